@@ -1,4 +1,6 @@
 fn main() {
+    println!("cargo:rustc-check-cfg=cfg(COMPILING_PLATFORM, values(\"UNIX\", \"WINDOWS\"))");
+
     #[cfg(not(windows))]
     println!("cargo:rustc-cfg=COMPILING_PLATFORM=\"UNIX\"");
 
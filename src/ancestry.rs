@@ -0,0 +1,297 @@
+//! Keeps track of every creature in a run and who they came from, and exports family trees as Graphviz DOT
+
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    fmt::Write,
+    fs, io,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use uuid::Uuid;
+
+use crate::creature::{Creature, Lineage};
+
+/// The directory family trees are exported to by default, relative to the working directory
+pub const ANCESTRY_DIRECTORY: &str = "ancestry";
+
+/// What's remembered about a creature after it's gone
+#[derive(Debug, Clone)]
+pub struct AncestryRecord {
+    pub id: Uuid,
+    pub lineage: Lineage,
+    /// The creature's score (distance), once its generation has been judged
+    pub score: Option<f32>,
+}
+
+/// Every creature of a run, keyed by id
+#[derive(Default, Clone)]
+pub struct Ancestry {
+    records: HashMap<Uuid, AncestryRecord>,
+    children: HashMap<Uuid, Vec<Uuid>>,
+}
+
+impl Ancestry {
+    /// Creates an empty ancestry
+    pub fn new() -> Ancestry {
+        Self::default()
+    }
+
+    /// Remembers a creature and links it to its parents
+    pub fn record(&mut self, creature: &Creature) {
+        let id = *creature.id();
+
+        for parent_id in creature.lineage().parent_ids() {
+            self.children.entry(*parent_id).or_default().push(id);
+        }
+
+        self.records.insert(
+            id,
+            AncestryRecord {
+                id,
+                lineage: creature.lineage().clone(),
+                score: None,
+            },
+        );
+    }
+
+    /// Sets the score (distance) of a recorded creature
+    pub fn set_score(&mut self, id: Uuid, score: f32) {
+        if let Some(record) = self.records.get_mut(&id) {
+            record.score = Some(score);
+        }
+    }
+
+    /// Gets the record of a creature
+    pub fn get(&self, id: &Uuid) -> Option<&AncestryRecord> {
+        self.records.get(id)
+    }
+
+    /// Gets how many creatures have been recorded
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    /// Returns true if no creatures have been recorded
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    /// Gets the recorded ancestors of a creature, closest first, not including the creature itself
+    pub fn ancestors(&self, id: &Uuid) -> Vec<Uuid> {
+        self.walk(id, |id| {
+            self.records
+                .get(id)
+                .map(|record| record.lineage.parent_ids().clone())
+                .unwrap_or_default()
+        })
+    }
+
+    /// Gets the recorded descendants of a creature, closest first, not including the creature itself
+    pub fn descendants(&self, id: &Uuid) -> Vec<Uuid> {
+        self.walk(id, |id| self.children.get(id).cloned().unwrap_or_default())
+    }
+
+    /// Gets the earliest recorded ancestor of a creature, following first parents, which is the creature
+    /// itself if none of its parents were recorded
+    pub fn founder(&self, id: &Uuid) -> Uuid {
+        let mut founder = *id;
+
+        while let Some(parent_id) = self
+            .records
+            .get(&founder)
+            .and_then(|record| record.lineage.parent_id())
+            .filter(|parent_id| self.records.contains_key(parent_id))
+        {
+            founder = *parent_id;
+        }
+
+        founder
+    }
+
+    /// Gets how many of the founding creatures (those without recorded parents) have a living descendant,
+    /// along with the total number of founders
+    pub fn surviving_founders(&self, living: &[Uuid]) -> (usize, usize) {
+        let founders = self
+            .records
+            .values()
+            .filter(|record| {
+                !record
+                    .lineage
+                    .parent_ids()
+                    .iter()
+                    .any(|parent_id| self.records.contains_key(parent_id))
+            })
+            .count();
+        let surviving: HashSet<Uuid> = living.iter().map(|id| self.founder(id)).collect();
+
+        (surviving.len(), founders)
+    }
+
+    /// Visits everything reachable from a creature through a function, breadth first
+    fn walk(&self, id: &Uuid, next: impl Fn(&Uuid) -> Vec<Uuid>) -> Vec<Uuid> {
+        let mut visited = HashSet::from([*id]);
+        let mut queue = VecDeque::from([*id]);
+        let mut found = Vec::new();
+
+        while let Some(current) = queue.pop_front() {
+            for next_id in next(&current) {
+                if visited.insert(next_id) {
+                    found.push(next_id);
+                    queue.push_back(next_id);
+                }
+            }
+        }
+
+        found
+    }
+
+    /// Writes the family tree of a creature, its ancestors and descendants, as a Graphviz DOT graph. The
+    /// creature and the living creatures are highlighted
+    pub fn family_tree_to_dot(&self, id: &Uuid, living: &[Uuid]) -> String {
+        let mut ids = self.ancestors(id);
+
+        ids.push(*id);
+        ids.extend(self.descendants(id));
+
+        self.to_dot(ids, Some(id), living)
+    }
+
+    /// Writes every recorded creature as a Graphviz DOT graph, highlighting the living creatures
+    pub fn run_to_dot(&self, living: &[Uuid]) -> String {
+        self.to_dot(self.records.keys().copied().collect(), None, living)
+    }
+
+    /// Writes a set of creatures and the links between them as a Graphviz DOT graph
+    fn to_dot(&self, mut ids: Vec<Uuid>, focus: Option<&Uuid>, living: &[Uuid]) -> String {
+        // Sorted by birth then id so the output is stable
+        ids.retain(|id| self.records.contains_key(id));
+        ids.sort_by_key(|id| (self.records[id].lineage.birth_generation(), *id));
+
+        let included: HashSet<&Uuid> = ids.iter().collect();
+        let mut dot = String::from(
+            "digraph ancestry {\n    rankdir=LR;\n    node [shape=box, fontname=\"sans-serif\"];\n",
+        );
+
+        // Writing to a String can't fail, so the results are ignored
+        for id in &ids {
+            let record = &self.records[id];
+            let score = match record.score {
+                Some(score) => format!("{:.2}m", score),
+                None => "unjudged".to_string(),
+            };
+            let style = if Some(id) == focus {
+                ", style=filled, fillcolor=gold"
+            } else if living.contains(id) {
+                ", style=filled, fillcolor=palegreen"
+            } else {
+                ""
+            };
+
+            let _ = writeln!(
+                dot,
+                "    \"{}\" [label=\"{}\\ngeneration {}\\n{}\"{}];",
+                id,
+                short_id(id),
+                record.lineage.birth_generation(),
+                score,
+                style
+            );
+        }
+
+        for id in &ids {
+            let lineage = &self.records[id].lineage;
+            let mutations: Vec<&str> = lineage
+                .mutations()
+                .iter()
+                .map(|mutation| mutation.name())
+                .collect();
+
+            for parent_id in lineage.parent_ids() {
+                if included.contains(parent_id) {
+                    let _ = writeln!(
+                        dot,
+                        "    \"{}\" -> \"{}\" [label=\"{}\"];",
+                        parent_id,
+                        id,
+                        mutations.join(", ")
+                    );
+                }
+            }
+        }
+
+        dot.push_str("}\n");
+
+        dot
+    }
+}
+
+/// Gets the first 8 characters of an id, which is enough to tell creatures apart at a glance
+pub fn short_id(id: &Uuid) -> String {
+    id.to_string()[..8].to_string()
+}
+
+/// Gets a path inside [ANCESTRY_DIRECTORY] to export a graph to, named after what it shows and the current time
+pub fn default_dot_path(name: &str) -> PathBuf {
+    let seconds = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+
+    Path::new(ANCESTRY_DIRECTORY).join(format!("{}-{}.dot", name, seconds))
+}
+
+/// Writes a DOT graph to a file, creating any missing directories
+pub fn save_dot(dot: &str, path: &Path) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    fs::write(path, dot)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::creature::CreatureBuilder;
+
+    #[test]
+    pub fn ancestry() {
+        let founder = CreatureBuilder::random().add_birth_generation(1).build();
+        let other_founder = CreatureBuilder::random().add_birth_generation(1).build();
+        let child = CreatureBuilder::mutate(&founder)
+            .add_birth_generation(2)
+            .build();
+        let grandchild = CreatureBuilder::mutate(&child)
+            .add_birth_generation(3)
+            .build();
+
+        let mut ancestry = Ancestry::new();
+
+        for creature in [&founder, &other_founder, &child, &grandchild] {
+            ancestry.record(creature);
+        }
+
+        ancestry.set_score(*child.id(), 1.5);
+
+        assert_eq!(ancestry.len(), 4);
+        assert_eq!(
+            ancestry.ancestors(grandchild.id()),
+            vec![*child.id(), *founder.id()]
+        );
+        assert_eq!(
+            ancestry.descendants(founder.id()),
+            vec![*child.id(), *grandchild.id()]
+        );
+        assert_eq!(ancestry.founder(grandchild.id()), *founder.id());
+        assert_eq!(ancestry.surviving_founders(&[*grandchild.id()]), (1, 2));
+
+        let dot = ancestry.family_tree_to_dot(child.id(), &[*grandchild.id()]);
+
+        assert!(dot.starts_with("digraph"));
+        assert_eq!(dot.matches(" -> ").count(), 2);
+        assert!(dot.contains("1.50m"));
+        assert!(!dot.contains(&other_founder.id().to_string()));
+        assert_eq!(ancestry.run_to_dot(&[]).matches("[label=").count(), 4 + 2);
+    }
+}
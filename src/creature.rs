@@ -1,21 +1,36 @@
 //! Contains the [Creature] struct and all related components of it
 
+mod brain;
 #[allow(clippy::module_inception)]
 mod creature_colors;
+mod lineage;
 mod movement_parameters;
 mod muscle;
+mod mutation_settings;
 mod node;
 mod position;
 
+pub use brain::{Brain, Controller, NeuralNetwork, Senses};
 pub use creature_colors::CreatureColors;
-pub use movement_parameters::MovementParameters;
+pub use lineage::{Lineage, Mutation};
+pub use movement_parameters::{
+    MovementParameters, Waveform, CONTRACTION_PERIOD_RANGE, EXTENSION_PERIOD_RANGE,
+};
 pub use muscle::Muscle;
+pub use mutation_settings::MutationSettings;
 pub use node::Node;
 pub use position::Position;
 
-use std::{collections::HashMap, ops::RangeInclusive};
+use std::{
+    collections::HashMap,
+    fs::{self, File},
+    io::{self, BufReader, BufWriter},
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
+};
 
 use rand::Rng;
+use serde::{Deserialize, Serialize};
 use uuid::{self, Uuid};
 
 use crate::util;
@@ -26,14 +41,25 @@ const RANDOM_NODE_X_POSITION_RANGE: RangeInclusive<f32> = -100.0..=100.0;
 const RANDOM_NODE_Y_POSITION_RANGE: RangeInclusive<f32> = -100.0..=100.0;
 const RANDOM_NODE_SIZE_RANGE: RangeInclusive<f32> = 10.0..=20.0;
 const RANDOM_CHANGE_TO_CONNECT_NODES: f32 = 0.75;
+const RANDOM_CHANCE_FOR_NEURAL_CONTROLLER: f32 = 0.25;
+
+/// The directory creatures are saved to by default, relative to the working directory
+pub const CREATURES_DIRECTORY: &str = "creatures";
+/// The extension of saved creature files
+pub const CREATURE_FILE_EXTENSION: &str = "creature.json";
 
 /// A creature, made up of [Node]s and [Muscle]s. Contains a unique id for reference. Built using a [CreatureBuilder].
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(try_from = "UncheckedCreature")]
 pub struct Creature {
     id: Uuid,
     nodes: HashMap<Uuid, Node>,
     muscles: HashMap<Uuid, Muscle>,
     movement_parameters: HashMap<Uuid, MovementParameters>,
+    controller: Controller,
     colors: CreatureColors,
+    #[serde(default)]
+    lineage: Lineage,
 }
 
 impl Creature {
@@ -57,10 +83,103 @@ impl Creature {
         &self.movement_parameters
     }
 
+    /// Returns the [Controller] that drives the [Creature]'s [Muscle]s
+    pub fn controller(&self) -> &Controller {
+        &self.controller
+    }
+
+    /// Returns where the [Creature] came from
+    pub fn lineage(&self) -> &Lineage {
+        &self.lineage
+    }
+
+    /// Returns the id of the [Creature] this one was mutated from, if any
+    pub fn parent_id(&self) -> Option<&Uuid> {
+        self.lineage.parent_id()
+    }
+
     /// Returns the node color
     pub fn colors(&self) -> &CreatureColors {
         &self.colors
     }
+
+    /// Gets the default path to save the [Creature] to, inside [CREATURES_DIRECTORY]
+    pub fn default_path(&self) -> PathBuf {
+        Path::new(CREATURES_DIRECTORY).join(format!("{}.{}", self.id, CREATURE_FILE_EXTENSION))
+    }
+
+    /// Saves the [Creature] to a file, creating any missing directories
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let writer = BufWriter::new(File::create(path)?);
+
+        serde_json::to_writer(writer, self).map_err(io::Error::from)
+    }
+
+    /// Loads a [Creature] from a file
+    pub fn load(path: &Path) -> io::Result<Creature> {
+        let reader = BufReader::new(File::open(path)?);
+
+        serde_json::from_reader(reader).map_err(io::Error::from)
+    }
+
+    /// Loads every [Creature] saved in a directory, skipping files without the [CREATURE_FILE_EXTENSION]
+    pub fn load_directory(directory: &Path) -> io::Result<Vec<Creature>> {
+        let suffix = format!(".{}", CREATURE_FILE_EXTENSION);
+        let mut paths = Vec::new();
+
+        for entry in fs::read_dir(directory)? {
+            let path = entry?.path();
+
+            if path.to_string_lossy().ends_with(&suffix) {
+                paths.push(path);
+            }
+        }
+
+        // Sorted so creatures load in the same order every time
+        paths.sort();
+
+        paths.iter().map(|path| Creature::load(path)).collect()
+    }
+}
+
+/// A [Creature] as saved, before its [Controller] is checked against its body
+#[derive(Deserialize)]
+struct UncheckedCreature {
+    id: Uuid,
+    nodes: HashMap<Uuid, Node>,
+    muscles: HashMap<Uuid, Muscle>,
+    movement_parameters: HashMap<Uuid, MovementParameters>,
+    controller: Controller,
+    colors: CreatureColors,
+    #[serde(default)]
+    lineage: Lineage,
+}
+
+impl TryFrom<UncheckedCreature> for Creature {
+    type Error = String;
+
+    fn try_from(creature: UncheckedCreature) -> Result<Self, Self::Error> {
+        if !creature.controller.fits(&creature.nodes, &creature.muscles) {
+            return Err(format!(
+                "The brain of creature {} doesn't match its nodes and muscles",
+                creature.id
+            ));
+        }
+
+        Ok(Creature {
+            id: creature.id,
+            nodes: creature.nodes,
+            muscles: creature.muscles,
+            movement_parameters: creature.movement_parameters,
+            controller: creature.controller,
+            colors: creature.colors,
+            lineage: creature.lineage,
+        })
+    }
 }
 
 /// Builds a [Creature]
@@ -69,24 +188,32 @@ pub struct CreatureBuilder {
     nodes: HashMap<Uuid, Node>,
     muscles: HashMap<Uuid, Muscle>,
     movement_parameters: Option<HashMap<Uuid, MovementParameters>>,
+    controller: Option<Controller>,
     colors: Option<CreatureColors>,
+    parent_ids: Vec<Uuid>,
+    birth_generation: usize,
+    mutations: Vec<Mutation>,
 }
 
 impl CreatureBuilder {
     /// Creates a [CreatureBuilder]
     pub fn new() -> CreatureBuilder {
         CreatureBuilder {
-            id: Uuid::new_v4(),
+            id: util::new_uuid(),
             nodes: HashMap::new(),
             muscles: HashMap::new(),
             movement_parameters: None,
+            controller: None,
             colors: None,
+            parent_ids: Vec::new(),
+            birth_generation: 0,
+            mutations: Vec::new(),
         }
     }
 
     /// Creates a [CreatureBuilder], and adds random nodes and muscles
     pub fn random() -> CreatureBuilder {
-        let mut rng = rand::thread_rng();
+        let mut rng = util::rng();
 
         let mut creature_builder = Self::new();
 
@@ -110,9 +237,10 @@ impl CreatureBuilder {
         let mut tested: HashMap<(Uuid, Uuid), bool> = HashMap::new();
 
         let mut muscles = Vec::new();
+        let nodes = util::sorted_by_id(&creature_builder.nodes);
 
-        for from in creature_builder.nodes.values() {
-            for to in creature_builder.nodes.values() {
+        for (_, from) in &nodes {
+            for (_, to) in &nodes {
                 if from.id == to.id || tested.contains_key(&(to.id, from.id)) {
                     continue;
                 }
@@ -131,20 +259,52 @@ impl CreatureBuilder {
             creature_builder = creature_builder.add_muscle(muscle)
         }
 
+        if rng.gen::<f32>() < RANDOM_CHANCE_FOR_NEURAL_CONTROLLER {
+            let brain = Brain::random(&creature_builder.nodes, &creature_builder.muscles);
+
+            creature_builder = creature_builder.add_controller(Controller::Neural(brain));
+        }
+
         creature_builder
     }
 
+    /// Creates a [CreatureBuilder] with everything from a previous [Creature], including its ids
+    pub fn copy(creature: &Creature) -> CreatureBuilder {
+        CreatureBuilder {
+            id: creature.id,
+            nodes: creature.nodes.clone(),
+            muscles: creature.muscles.clone(),
+            movement_parameters: Some(creature.movement_parameters.clone()),
+            controller: Some(creature.controller.clone()),
+            colors: Some(creature.colors),
+            parent_ids: creature.lineage.parent_ids().clone(),
+            birth_generation: creature.lineage.birth_generation(),
+            mutations: creature.lineage.mutations().clone(),
+        }
+    }
+
     /// Creates a [CreatureBuilder] by building off a previous [Creature] and mutating it.
     ///
     /// This method binds new Uuids to all objects out of necessity.
     pub fn mutate(creature: &Creature) -> CreatureBuilder {
+        Self::mutate_with(creature, MutationSettings::default())
+    }
+
+    /// Creates a [CreatureBuilder] by building off a previous [Creature] and mutating it, as often as the
+    /// [MutationSettings] say to.
+    ///
+    /// This method binds new Uuids to all objects out of necessity.
+    pub fn mutate_with(creature: &Creature, settings: MutationSettings) -> CreatureBuilder {
+        let mut rng = util::rng();
         let mut builder = CreatureBuilder::new();
 
+        builder.parent_ids = vec![creature.id];
+
         // Need to map old uuids to the new ones
         let mut old_uuid_to_new_uuid: HashMap<Uuid, Uuid> = HashMap::new();
 
-        // Duplicate nodes
-        for (old_id, node) in creature.nodes() {
+        // Duplicate nodes, in order so the same random numbers always go to the same parts
+        for (old_id, node) in util::sorted_by_id(creature.nodes()) {
             let new_node = Node::new(node.position, node.size);
 
             old_uuid_to_new_uuid.insert(*old_id, new_node.id);
@@ -154,25 +314,70 @@ impl CreatureBuilder {
 
         // Duplicate muscles and movement parameters
         let mut movement_parameters = HashMap::new();
+        let mut movement_changed = false;
+        let mut waveforms_changed = false;
 
-        for (old_id, muscle) in creature.muscles() {
+        for (old_id, muscle) in util::sorted_by_id(creature.muscles()) {
             let new_muscle = Muscle::new(
                 old_uuid_to_new_uuid[&muscle.from_id],
                 old_uuid_to_new_uuid[&muscle.to_id],
             );
+            let old_movement_parameters = &creature.movement_parameters()[old_id];
+            let new_movement_parameters = if rng.gen::<f32>() < settings.muscle_chance {
+                movement_changed = true;
 
-            movement_parameters.insert(
-                new_muscle.id,
-                MovementParameters::mutate(&creature.movement_parameters()[old_id]),
-            );
+                MovementParameters::mutate(old_movement_parameters)
+            } else {
+                old_movement_parameters.clone()
+            };
+
+            waveforms_changed |=
+                old_movement_parameters.waveform() != new_movement_parameters.waveform();
+
+            movement_parameters.insert(new_muscle.id, new_movement_parameters);
+
+            old_uuid_to_new_uuid.insert(*old_id, new_muscle.id);
 
             builder = builder.add_muscle(new_muscle);
         }
 
-        // Add MovementParameters and CharacterColors, then return
+        // Mutate the controller, occasionally switching to the other kind
+        let switch_controller = rng.gen::<f32>() < settings.controller_switch_chance;
+        let (controller, controller_mutation) = match (&creature.controller, switch_controller) {
+            (Controller::Timed, false) => (Controller::Timed, None),
+            (Controller::Neural(_), true) => (Controller::Timed, Some(Mutation::SwitchedToTimed)),
+            (Controller::Timed, true) => (
+                Controller::Neural(Brain::random(&builder.nodes, &builder.muscles)),
+                Some(Mutation::SwitchedToNeural),
+            ),
+            (Controller::Neural(brain), false) => (
+                Controller::Neural(Brain::mutate(brain, &old_uuid_to_new_uuid)),
+                Some(Mutation::Brain),
+            ),
+        };
+
+        let colors = CreatureColors::mutate(&creature.colors);
+
+        // Record what changed
+        if movement_changed {
+            builder.mutations.push(Mutation::MovementParameters);
+        }
+
+        if waveforms_changed {
+            builder.mutations.push(Mutation::Waveforms);
+        }
+
+        builder.mutations.extend(controller_mutation);
+
+        if colors.hue() != creature.colors.hue() {
+            builder.mutations.push(Mutation::Colors);
+        }
+
+        // Add MovementParameters, Controller and CharacterColors, then return
         builder
             .add_movement_parameters(movement_parameters)
-            .add_colors(CreatureColors::mutate(&creature.colors))
+            .add_controller(controller)
+            .add_colors(colors)
     }
 
     /// Adds a [Node] to the [Creature]
@@ -199,6 +404,13 @@ impl CreatureBuilder {
         self
     }
 
+    /// Sets the [Controller] of the [Creature]. Defaults to [Controller::Timed]
+    pub fn add_controller(mut self, controller: Controller) -> CreatureBuilder {
+        self.controller = Some(controller);
+
+        self
+    }
+
     /// Sets the [colors](CreatureColors) of the [Creature]
     pub fn add_colors(mut self, colors: CreatureColors) -> CreatureBuilder {
         self.colors = Some(colors);
@@ -206,6 +418,13 @@ impl CreatureBuilder {
         self
     }
 
+    /// Sets the generation the [Creature] is born in. Defaults to 0, for creatures made outside of an evolver
+    pub fn add_birth_generation(mut self, birth_generation: usize) -> CreatureBuilder {
+        self.birth_generation = birth_generation;
+
+        self
+    }
+
     /// Gets the bounds of the [Creature], represented by the top left and bottom right
     fn get_bounds(&self) -> (Position, Position) {
         let x_pos_iter = self.nodes.values().map(|node| node.position.x);
@@ -261,14 +480,21 @@ impl CreatureBuilder {
             MovementParameters::generate_for_muscles_and_nodes(&self.muscles, &self.nodes)
         });
 
-        let colors = self.colors.unwrap_or_else(CreatureColors::new);
+        // A brain that doesn't match the body, like after removing a node, can't drive it
+        let controller = match self.controller {
+            Some(controller) if controller.fits(&self.nodes, &self.muscles) => controller,
+            _ => Controller::Timed,
+        };
+        let colors = self.colors.unwrap_or_default();
 
         Creature {
             id: self.id,
             nodes: self.nodes,
             muscles: self.muscles,
             movement_parameters,
+            controller,
             colors,
+            lineage: Lineage::new(self.parent_ids, self.birth_generation, self.mutations),
         }
     }
 }
@@ -290,14 +516,14 @@ mod tests {
         let node2 = Node::new(Position::new(2.0, 1.0), 3.0);
         let node3 = Node::new(Position::new(5.0, 5.0), 3.0);
 
-        let id1 = *&node1.id;
-        let id2 = *&node2.id;
-        let id3 = *&node3.id;
+        let id1 = node1.id;
+        let id2 = node2.id;
+        let id3 = node3.id;
 
         let muscle1 = Muscle::new(id1, id2);
         let muscle2 = Muscle::new(id2, id3);
 
-        let id4 = *&muscle1.id;
+        let id4 = muscle1.id;
 
         let c = CreatureBuilder::new()
             .add_node(node1)
@@ -313,5 +539,15 @@ mod tests {
             c.muscles().get(&id4).unwrap().to_id,
             c.nodes.get(&id2).unwrap().id
         );
+        assert_eq!(c.parent_id(), None);
+
+        let child = CreatureBuilder::mutate(&c).add_birth_generation(2).build();
+
+        assert_eq!(child.parent_id(), Some(c.id()));
+        assert_eq!(child.lineage().birth_generation(), 2);
+        assert!(child
+            .lineage()
+            .mutations()
+            .contains(&Mutation::MovementParameters));
     }
 }
@@ -0,0 +1,413 @@
+use std::{
+    collections::{HashMap, HashSet},
+    f32::consts::PI,
+    ops::RangeInclusive,
+};
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{simulation::STEPS_PER_SECOND, util};
+
+use super::{Muscle, Node};
+
+const HIDDEN_NEURONS: usize = 8;
+const EXTRA_INPUTS: usize = 3; // Clock sine, clock cosine and body tilt
+const RANDOM_WEIGHT_RANGE: RangeInclusive<f32> = -1.0..=1.0;
+const WEIGHT_RANGE: RangeInclusive<f32> = -4.0..=4.0;
+const MUTATE_WEIGHT_RANGE: RangeInclusive<f32> = -0.2..=0.2;
+const CHANCE_TO_MUTATE_WEIGHT: f32 = 0.2;
+const CLOCK_PERIOD_RANGE: RangeInclusive<i32> = STEPS_PER_SECOND / 4..=STEPS_PER_SECOND * 4;
+const MUTATE_CLOCK_PERIOD_RANGE: RangeInclusive<i32> =
+    -STEPS_PER_SECOND / 30..=STEPS_PER_SECOND / 30;
+
+/// What a [Brain] can sense about its body at a single step
+pub struct Senses {
+    /// Whether each node is touching the ground, in the order of [Brain::node_ids]
+    pub ground_contacts: Vec<bool>,
+    /// How stretched each muscle is relative to its normal length (0.0 = normal), in the order of [Brain::muscle_ids]
+    pub muscle_lengths: Vec<f32>,
+    /// How far the body has rotated from its starting orientation, in half turns
+    pub tilt: f32,
+}
+
+/// A small recurrent neural network, where each hidden neuron sees the inputs and the hidden layer's previous activations
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NeuralNetwork {
+    input_count: usize,
+    hidden_count: usize,
+    output_count: usize,
+    hidden_weights: Vec<f32>, // hidden_count rows of (inputs, previous hidden, bias)
+    output_weights: Vec<f32>, // output_count rows of (hidden, bias)
+}
+
+impl NeuralNetwork {
+    /// Creates a network with random weights
+    pub fn random(input_count: usize, hidden_count: usize, output_count: usize) -> NeuralNetwork {
+        let mut rng = util::rng();
+        let hidden_weight_count = hidden_count * (input_count + hidden_count + 1);
+        let output_weight_count = output_count * (hidden_count + 1);
+
+        NeuralNetwork {
+            input_count,
+            hidden_count,
+            output_count,
+            hidden_weights: (0..hidden_weight_count)
+                .map(|_| rng.gen_range(RANDOM_WEIGHT_RANGE))
+                .collect(),
+            output_weights: (0..output_weight_count)
+                .map(|_| rng.gen_range(RANDOM_WEIGHT_RANGE))
+                .collect(),
+        }
+    }
+
+    /// Creates a new [NeuralNetwork] that is a mutation of the passed in one
+    pub fn mutate(network: &NeuralNetwork) -> NeuralNetwork {
+        let mut rng = util::rng();
+        let mut mutate_weight = |weight: &f32| {
+            if rng.gen::<f32>() >= CHANCE_TO_MUTATE_WEIGHT {
+                return *weight;
+            }
+
+            util::clamp_to_range(weight + rng.gen_range(MUTATE_WEIGHT_RANGE), WEIGHT_RANGE)
+        };
+
+        NeuralNetwork {
+            hidden_weights: network
+                .hidden_weights
+                .iter()
+                .map(&mut mutate_weight)
+                .collect(),
+            output_weights: network
+                .output_weights
+                .iter()
+                .map(&mut mutate_weight)
+                .collect(),
+            ..*network
+        }
+    }
+
+    /// The number of inputs the network takes
+    pub fn input_count(&self) -> usize {
+        self.input_count
+    }
+
+    /// The number of hidden neurons, which is also the size of the state passed into [NeuralNetwork::evaluate]
+    pub fn hidden_count(&self) -> usize {
+        self.hidden_count
+    }
+
+    /// The number of outputs the network produces
+    pub fn output_count(&self) -> usize {
+        self.output_count
+    }
+
+    /// Returns true if the network has as many weights as its sizes call for
+    pub fn is_consistent(&self) -> bool {
+        self.hidden_weights.len() == self.hidden_count * (self.input_count + self.hidden_count + 1)
+            && self.output_weights.len() == self.output_count * (self.hidden_count + 1)
+    }
+
+    /// Evaluates the network, updating the hidden state in place and returning outputs in [0, 1]
+    ///
+    /// # Panics
+    /// If the number of inputs or the size of the hidden state don't match the network
+    pub fn evaluate(&self, inputs: &[f32], hidden_state: &mut [f32]) -> Vec<f32> {
+        assert_eq!(inputs.len(), self.input_count);
+        assert_eq!(hidden_state.len(), self.hidden_count);
+
+        let row_length = self.input_count + self.hidden_count + 1;
+        let new_hidden_state: Vec<f32> = self
+            .hidden_weights
+            .chunks(row_length)
+            .map(|row| {
+                let (input_weights, rest) = row.split_at(self.input_count);
+                let (recurrent_weights, bias) = rest.split_at(self.hidden_count);
+
+                let sum =
+                    dot(input_weights, inputs) + dot(recurrent_weights, hidden_state) + bias[0];
+
+                sum.tanh()
+            })
+            .collect();
+
+        hidden_state.copy_from_slice(&new_hidden_state);
+
+        self.output_weights
+            .chunks(self.hidden_count + 1)
+            .map(|row| {
+                let (weights, bias) = row.split_at(self.hidden_count);
+
+                sigmoid(dot(weights, hidden_state) + bias[0])
+            })
+            .collect()
+    }
+}
+
+/// An evolvable controller that drives a [Creature](super::Creature)'s muscles from what it senses
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Brain {
+    node_ids: Vec<Uuid>,
+    muscle_ids: Vec<Uuid>,
+    clock_period: i32,
+    network: NeuralNetwork,
+}
+
+impl Brain {
+    /// Creates a brain with random weights, sensing the passed in nodes and controlling the passed in muscles
+    pub fn random(nodes: &HashMap<Uuid, Node>, muscles: &HashMap<Uuid, Muscle>) -> Brain {
+        let mut rng = util::rng();
+        let node_ids: Vec<Uuid> = util::sorted_by_id(nodes)
+            .into_iter()
+            .map(|(id, _)| *id)
+            .collect();
+        let muscle_ids: Vec<Uuid> = util::sorted_by_id(muscles)
+            .into_iter()
+            .map(|(id, _)| *id)
+            .collect();
+
+        let network = NeuralNetwork::random(
+            EXTRA_INPUTS + node_ids.len() + muscle_ids.len(),
+            HIDDEN_NEURONS,
+            muscle_ids.len(),
+        );
+
+        Brain {
+            node_ids,
+            muscle_ids,
+            clock_period: rng.gen_range(CLOCK_PERIOD_RANGE),
+            network,
+        }
+    }
+
+    /// Creates a new [Brain] that is a mutation of the passed in one, remapping node and muscle ids using `old_id_to_new_id`
+    pub fn mutate(brain: &Brain, old_id_to_new_id: &HashMap<Uuid, Uuid>) -> Brain {
+        let mut rng = util::rng();
+
+        Brain {
+            node_ids: brain
+                .node_ids
+                .iter()
+                .map(|id| old_id_to_new_id[id])
+                .collect(),
+            muscle_ids: brain
+                .muscle_ids
+                .iter()
+                .map(|id| old_id_to_new_id[id])
+                .collect(),
+            clock_period: util::clamp_to_range(
+                brain.clock_period + rng.gen_range(MUTATE_CLOCK_PERIOD_RANGE),
+                CLOCK_PERIOD_RANGE,
+            ),
+            network: NeuralNetwork::mutate(&brain.network),
+        }
+    }
+
+    /// Returns true if the brain senses exactly the passed in nodes and controls exactly the passed in muscles,
+    /// with a network sized to match. Brains from hand-edited or older files might not
+    pub fn fits(&self, nodes: &HashMap<Uuid, Node>, muscles: &HashMap<Uuid, Muscle>) -> bool {
+        matches_ids(&self.node_ids, nodes)
+            && matches_ids(&self.muscle_ids, muscles)
+            && self.clock_period > 0
+            && self.network.is_consistent()
+            && self.network.input_count() == EXTRA_INPUTS + nodes.len() + muscles.len()
+            && self.network.output_count() == muscles.len()
+    }
+
+    /// The ids of the nodes the brain senses, in input order
+    pub fn node_ids(&self) -> &Vec<Uuid> {
+        &self.node_ids
+    }
+
+    /// The ids of the muscles the brain controls, in output order
+    pub fn muscle_ids(&self) -> &Vec<Uuid> {
+        &self.muscle_ids
+    }
+
+    /// The period of the brain's internal clock, in steps
+    pub fn clock_period(&self) -> i32 {
+        self.clock_period
+    }
+
+    /// The brain's network
+    pub fn network(&self) -> &NeuralNetwork {
+        &self.network
+    }
+
+    /// Creates a fresh hidden state to pass into [Brain::think]
+    pub fn new_state(&self) -> Vec<f32> {
+        vec![0.0; self.network.hidden_count()]
+    }
+
+    /// Decides the extension delta of each muscle (keyed by id), where 0.0 is fully contracted and 1.0 is fully extended
+    pub fn think(&self, step: i32, senses: &Senses, state: &mut [f32]) -> HashMap<Uuid, f32> {
+        let clock = 2.0 * PI * step as f32 / self.clock_period as f32;
+
+        let mut inputs = vec![clock.sin(), clock.cos(), senses.tilt];
+        inputs.extend(
+            senses
+                .ground_contacts
+                .iter()
+                .map(|touching| if *touching { 1.0 } else { 0.0 }),
+        );
+        inputs.extend(&senses.muscle_lengths);
+
+        let outputs = self.network.evaluate(&inputs, state);
+
+        self.muscle_ids.iter().copied().zip(outputs).collect()
+    }
+}
+
+/// Selects what drives a [Creature](super::Creature)'s muscles
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub enum Controller {
+    /// Open-loop timing from each muscle's [MovementParameters](super::MovementParameters)
+    #[default]
+    Timed,
+    /// Closed-loop control from a [Brain]
+    Neural(Brain),
+}
+
+impl Controller {
+    /// The display name of the controller
+    pub fn name(&self) -> &'static str {
+        match self {
+            Controller::Timed => "Timed",
+            Controller::Neural(_) => "Neural",
+        }
+    }
+
+    /// Returns true if the controller can drive a body made of the passed in nodes and muscles, see [Brain::fits]
+    pub fn fits(&self, nodes: &HashMap<Uuid, Node>, muscles: &HashMap<Uuid, Muscle>) -> bool {
+        match self {
+            Controller::Timed => true,
+            Controller::Neural(brain) => brain.fits(nodes, muscles),
+        }
+    }
+}
+
+/// Returns true if a list has every key of a map exactly once
+fn matches_ids<T>(ids: &[Uuid], map: &HashMap<Uuid, T>) -> bool {
+    ids.len() == map.len()
+        && ids.iter().collect::<HashSet<_>>().len() == ids.len()
+        && ids.iter().all(|id| map.contains_key(id))
+}
+
+/// Computes the dot product of two slices
+fn dot(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b).map(|(a, b)| a * b).sum()
+}
+
+/// Squashes a number into (0, 1)
+fn sigmoid(x: f32) -> f32 {
+    1.0 / (1.0 + (-x).exp())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        creature::{Creature, CreatureBuilder, MutationSettings, Position},
+        simulation::Simulation,
+    };
+
+    /// Builds a triangle of nodes and muscles driven by a random brain
+    fn neural_triangle() -> Creature {
+        let nodes = [
+            Node::new(Position::new(0.0, 0.0), 10.0),
+            Node::new(Position::new(50.0, 0.0), 10.0),
+            Node::new(Position::new(25.0, -40.0), 10.0),
+        ];
+        let muscles = [
+            Muscle::new(nodes[0].id, nodes[1].id),
+            Muscle::new(nodes[1].id, nodes[2].id),
+            Muscle::new(nodes[2].id, nodes[0].id),
+        ];
+        let mut builder = CreatureBuilder::new();
+
+        for node in nodes {
+            builder = builder.add_node(node);
+        }
+
+        for muscle in muscles {
+            builder = builder.add_muscle(muscle);
+        }
+
+        let body = builder.build();
+        let brain = Brain::random(body.nodes(), body.muscles());
+
+        CreatureBuilder::copy(&body)
+            .add_controller(Controller::Neural(brain))
+            .build()
+    }
+
+    #[test]
+    pub fn evaluate() {
+        let network = NeuralNetwork::random(3, 4, 2);
+        let mut state = vec![0.0; network.hidden_count()];
+
+        let outputs = network.evaluate(&[1.0, -1.0, 0.5], &mut state);
+
+        assert_eq!(outputs.len(), 2);
+        assert!(outputs.iter().all(|output| (0.0..=1.0).contains(output)));
+        assert!(state.iter().all(|value| (-1.0..=1.0).contains(value)));
+    }
+
+    #[test]
+    pub fn neural_simulation() {
+        let creature = neural_triangle();
+
+        assert!(matches!(creature.controller(), Controller::Neural(_)));
+
+        let mut simulation = Simulation::new(creature);
+
+        for _ in 0..STEPS_PER_SECOND {
+            simulation.step();
+        }
+
+        assert!(simulation.get_score().is_finite());
+    }
+
+    #[test]
+    pub fn mutate() {
+        let creature = neural_triangle();
+        let settings = MutationSettings {
+            controller_switch_chance: 0.0,
+            ..Default::default()
+        };
+        let mutant = CreatureBuilder::mutate_with(&creature, settings).build();
+
+        // The brain senses and controls the mutant's own nodes and muscles, in the same order as the parent's
+        let (parent_brain, brain) = match (creature.controller(), mutant.controller()) {
+            (Controller::Neural(parent_brain), Controller::Neural(brain)) => (parent_brain, brain),
+            _ => panic!("Mutating a neural creature without switching should keep its brain"),
+        };
+
+        assert!(brain.fits(mutant.nodes(), mutant.muscles()));
+        assert_eq!(brain.node_ids().len(), parent_brain.node_ids().len());
+        assert!(brain
+            .node_ids()
+            .iter()
+            .all(|id| !creature.nodes().contains_key(id)));
+    }
+
+    #[test]
+    pub fn mismatched_brain() {
+        let creature = neural_triangle();
+        let other = neural_triangle();
+
+        // Built onto another body, the brain is dropped
+        let rebuilt = CreatureBuilder::copy(&other)
+            .add_controller(creature.controller().clone())
+            .build();
+
+        assert!(matches!(rebuilt.controller(), Controller::Timed));
+
+        // Loaded with another body's brain, the creature is rejected
+        let mut json = serde_json::to_value(&creature).unwrap();
+        json["controller"] = serde_json::to_value(other.controller()).unwrap();
+
+        assert!(serde_json::from_value::<Creature>(json).is_err());
+    }
+}
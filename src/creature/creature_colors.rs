@@ -1,13 +1,15 @@
 use crate::util;
 use egui::Color32;
 use rand::Rng;
+use serde::{Deserialize, Serialize};
 use std::ops::RangeInclusive;
 
 const COLOR_HUE_RANGE: RangeInclusive<u16> = 0..=350;
 const MUTATE_COLOR_HUE_RANGE: RangeInclusive<i16> = -10..=10;
 
-/// Represents the colors of a creature
-#[derive(Debug, Clone, Copy)]
+/// Represents the colors of a creature. Stored as just the hue, since everything else is derived from it
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(from = "u16", into = "u16")]
 pub struct CreatureColors {
     hue: u16,
     node: Color32,
@@ -19,7 +21,7 @@ pub struct CreatureColors {
 impl CreatureColors {
     /// Creates a new random set of creature colors
     pub fn new() -> CreatureColors {
-        let mut rng = rand::thread_rng();
+        let mut rng = util::rng();
 
         let hue = rng.gen_range(COLOR_HUE_RANGE);
 
@@ -44,7 +46,7 @@ impl CreatureColors {
 
     /// Creates a new [CreatureColors] that is a mutation of the one passed in
     pub fn mutate(colors: &CreatureColors) -> CreatureColors {
-        let mut rng = rand::thread_rng();
+        let mut rng = util::rng();
         let new_hue = (colors.hue() as i16 + rng.gen_range(MUTATE_COLOR_HUE_RANGE)) as u16 % 360;
 
         CreatureColors::from_hue(new_hue)
@@ -76,6 +78,20 @@ impl CreatureColors {
     }
 }
 
+impl From<u16> for CreatureColors {
+    /// Same as [CreatureColors::from_hue]
+    fn from(hue: u16) -> Self {
+        Self::from_hue(hue)
+    }
+}
+
+impl From<CreatureColors> for u16 {
+    /// Same as [CreatureColors::hue]
+    fn from(colors: CreatureColors) -> Self {
+        colors.hue()
+    }
+}
+
 impl Default for CreatureColors {
     /// Identical to [CreatureColors::new]
     fn default() -> Self {
@@ -0,0 +1,76 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Where a creature came from: its parents, when it was born and what changed from its parent
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Lineage {
+    parent_ids: Vec<Uuid>,
+    birth_generation: usize,
+    mutations: Vec<Mutation>,
+}
+
+impl Lineage {
+    /// Creates a lineage
+    pub fn new(
+        parent_ids: Vec<Uuid>,
+        birth_generation: usize,
+        mutations: Vec<Mutation>,
+    ) -> Lineage {
+        Lineage {
+            parent_ids,
+            birth_generation,
+            mutations,
+        }
+    }
+
+    /// The ids of the creatures this one was made from, which is empty for randomly generated creatures
+    pub fn parent_ids(&self) -> &Vec<Uuid> {
+        &self.parent_ids
+    }
+
+    /// The id of the first parent, if any
+    pub fn parent_id(&self) -> Option<&Uuid> {
+        self.parent_ids.first()
+    }
+
+    /// The generation the creature was born in, or 0 if it was made outside of an evolver
+    pub fn birth_generation(&self) -> usize {
+        self.birth_generation
+    }
+
+    /// The mutations applied to the parent to make this creature
+    pub fn mutations(&self) -> &Vec<Mutation> {
+        &self.mutations
+    }
+}
+
+/// A kind of change made to a creature when mutating it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Mutation {
+    /// The periods and phases of its muscles were nudged
+    MovementParameters,
+    /// At least one muscle's waveform changed
+    Waveforms,
+    /// The weights of its neural controller were nudged
+    Brain,
+    /// It switched from a timed to a neural controller
+    SwitchedToNeural,
+    /// It switched from a neural to a timed controller
+    SwitchedToTimed,
+    /// Its hue shifted
+    Colors,
+}
+
+impl Mutation {
+    /// A short name for the mutation
+    pub fn name(&self) -> &'static str {
+        match self {
+            Mutation::MovementParameters => "movement",
+            Mutation::Waveforms => "waveforms",
+            Mutation::Brain => "brain",
+            Mutation::SwitchedToNeural => "to neural",
+            Mutation::SwitchedToTimed => "to timed",
+            Mutation::Colors => "colors",
+        }
+    }
+}
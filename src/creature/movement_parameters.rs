@@ -1,6 +1,7 @@
-use std::{collections::HashMap, ops::RangeInclusive};
+use std::{collections::HashMap, f32::consts::PI, ops::RangeInclusive};
 
 use rand::Rng;
+use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 use crate::{simulation::STEPS_PER_SECOND, util};
@@ -9,42 +10,157 @@ use super::{Muscle, Node};
 
 type Range = RangeInclusive<i32>;
 
-const EXTENSION_PERIOD_RANGE: Range = STEPS_PER_SECOND / 4..=STEPS_PER_SECOND * 4;
-const CONTRACTION_PERIOD_RANGE: Range = STEPS_PER_SECOND / 4..=STEPS_PER_SECOND * 4;
+/// The range of steps a muscle can spend extending
+pub const EXTENSION_PERIOD_RANGE: Range = STEPS_PER_SECOND / 4..=STEPS_PER_SECOND * 4;
+/// The range of steps a muscle can spend contracting
+pub const CONTRACTION_PERIOD_RANGE: Range = STEPS_PER_SECOND / 4..=STEPS_PER_SECOND * 4;
 
 const MUTATE_EXTENSION_PERIOD_RANGE: Range = -STEPS_PER_SECOND / 30..=STEPS_PER_SECOND / 30;
 const MUTATE_CONTRACTION_PERIOD_RANGE: Range = -STEPS_PER_SECOND / 30..=STEPS_PER_SECOND / 30;
+const MUTATE_PHASE_OFFSET_RANGE: Range = -STEPS_PER_SECOND / 10..=STEPS_PER_SECOND / 10;
+
+const WARM_UP_STEPS: i32 = STEPS_PER_SECOND / 2; // Steps spent easing in from the normal length
+const KEYFRAME_COUNT_RANGE: RangeInclusive<usize> = 2..=8;
+const MUTATE_KEYFRAME_RANGE: RangeInclusive<f32> = -0.1..=0.1;
+const CHANCE_TO_CHANGE_WAVEFORM: f32 = 0.05;
+const CHANCE_TO_CHANGE_KEYFRAME_COUNT: f32 = 0.1;
+
+/// The shape of a muscle's extension over one cycle
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Waveform {
+    /// Eases between contracted and extended
+    Sine,
+    /// Snaps between contracted and extended
+    Square,
+    /// Moves linearly between contracted and extended
+    Triangle,
+    /// Linearly interpolates between evenly spaced extensions across the whole cycle, wrapping at the end
+    Keyframes(Vec<f32>),
+}
+
+impl Waveform {
+    /// Generates a random waveform
+    pub fn random() -> Waveform {
+        let mut rng = util::rng();
+
+        match rng.gen_range(0..4) {
+            0 => Waveform::Sine,
+            1 => Waveform::Square,
+            2 => Waveform::Triangle,
+            _ => {
+                let count = rng.gen_range(KEYFRAME_COUNT_RANGE);
+
+                Waveform::Keyframes((0..count).map(|_| rng.gen::<f32>()).collect())
+            }
+        }
+    }
+
+    /// Creates a new [Waveform] that is a mutation of the passed in one
+    pub fn mutate(waveform: &Waveform) -> Waveform {
+        let mut rng = util::rng();
+
+        if rng.gen::<f32>() < CHANCE_TO_CHANGE_WAVEFORM {
+            return Self::random();
+        }
+
+        match waveform {
+            Waveform::Keyframes(keyframes) => {
+                let mut keyframes: Vec<f32> = keyframes
+                    .iter()
+                    .map(|keyframe| {
+                        util::clamp_to_range(
+                            keyframe + rng.gen_range(MUTATE_KEYFRAME_RANGE),
+                            0.0..=1.0,
+                        )
+                    })
+                    .collect();
+
+                if rng.gen::<f32>() < CHANCE_TO_CHANGE_KEYFRAME_COUNT {
+                    if rng.gen::<bool>() {
+                        keyframes.push(rng.gen());
+                    } else {
+                        keyframes.pop();
+                    }
+
+                    keyframes.resize_with(
+                        util::clamp_to_range(keyframes.len(), KEYFRAME_COUNT_RANGE),
+                        || rng.gen(),
+                    );
+                }
+
+                Waveform::Keyframes(keyframes)
+            }
+            waveform => waveform.clone(),
+        }
+    }
+
+    /// The display name of the waveform
+    pub fn name(&self) -> &'static str {
+        match self {
+            Waveform::Sine => "Sine",
+            Waveform::Square => "Square",
+            Waveform::Triangle => "Triangle",
+            Waveform::Keyframes(_) => "Keyframes",
+        }
+    }
+}
 
 /// Represents a set of parameters for when and how a muscle should move, in steps
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MovementParameters {
     muscle_length: f32,
     extension_period: i32,
     contraction_period: i32,
+    phase_offset: i32,
+    waveform: Waveform,
 }
 
 impl MovementParameters {
+    /// Creates a new set of movement parameters, wrapping the phase offset into the cycle
+    pub fn new(
+        muscle_length: f32,
+        extension_period: i32,
+        contraction_period: i32,
+        phase_offset: i32,
+        waveform: Waveform,
+    ) -> MovementParameters {
+        let extension_period = util::clamp_to_range(extension_period, EXTENSION_PERIOD_RANGE);
+        let contraction_period = util::clamp_to_range(contraction_period, CONTRACTION_PERIOD_RANGE);
+
+        MovementParameters {
+            muscle_length,
+            extension_period,
+            contraction_period,
+            phase_offset: phase_offset.rem_euclid(extension_period + contraction_period),
+            waveform,
+        }
+    }
+
     /// Generates for a set of muscles and nodes
     pub fn generate_for_muscles_and_nodes(
         muscles: &HashMap<Uuid, Muscle>,
         nodes: &HashMap<Uuid, Node>,
     ) -> HashMap<Uuid, MovementParameters> {
-        let mut rng = rand::thread_rng();
+        let mut rng = util::rng();
         let mut id_to_movement_parameters = HashMap::new();
 
-        for (id, muscle) in muscles {
+        for (id, muscle) in util::sorted_by_id(muscles) {
             let from = &nodes.get(&muscle.from_id).unwrap().position;
             let to = &nodes.get(&muscle.to_id).unwrap().position;
             let muscle_length = from.distance_to(to);
 
+            let extension_period = rng.gen_range(EXTENSION_PERIOD_RANGE);
+            let contraction_period = rng.gen_range(CONTRACTION_PERIOD_RANGE);
+
             id_to_movement_parameters.insert(
                 *id,
-                MovementParameters {
+                MovementParameters::new(
                     muscle_length,
-
-                    extension_period: rng.gen_range(EXTENSION_PERIOD_RANGE),
-                    contraction_period: rng.gen_range(CONTRACTION_PERIOD_RANGE),
-                },
+                    extension_period,
+                    contraction_period,
+                    rng.gen_range(0..extension_period + contraction_period),
+                    Waveform::random(),
+                ),
             );
         }
 
@@ -53,24 +169,24 @@ impl MovementParameters {
 
     /// Creates a new MovementParameters that is a mutation of the passed in one
     pub fn mutate(movement_parameters: &MovementParameters) -> MovementParameters {
-        let mut rng = rand::thread_rng();
+        let mut rng = util::rng();
 
-        let new_extension_period = util::clamp_to_range(
-            movement_parameters.extension_period + rng.gen_range(MUTATE_EXTENSION_PERIOD_RANGE),
-            EXTENSION_PERIOD_RANGE,
-        );
+        let new_extension_period =
+            movement_parameters.extension_period + rng.gen_range(MUTATE_EXTENSION_PERIOD_RANGE);
 
-        let new_contraction_period = util::clamp_to_range(
-            movement_parameters.contraction_period + rng.gen_range(MUTATE_CONTRACTION_PERIOD_RANGE),
-            CONTRACTION_PERIOD_RANGE,
-        );
+        let new_contraction_period =
+            movement_parameters.contraction_period + rng.gen_range(MUTATE_CONTRACTION_PERIOD_RANGE);
 
-        MovementParameters {
-            muscle_length: movement_parameters.muscle_length,
+        let new_phase_offset =
+            movement_parameters.phase_offset + rng.gen_range(MUTATE_PHASE_OFFSET_RANGE);
 
-            extension_period: new_extension_period,
-            contraction_period: new_contraction_period,
-        }
+        MovementParameters::new(
+            movement_parameters.muscle_length,
+            new_extension_period,
+            new_contraction_period,
+            new_phase_offset,
+            Waveform::mutate(&movement_parameters.waveform),
+        )
     }
 
     /// Gets the normal muscle length
@@ -78,38 +194,145 @@ impl MovementParameters {
         self.muscle_length
     }
 
+    /// Gets the number of steps spent extending per cycle
+    pub fn extension_period(&self) -> i32 {
+        self.extension_period
+    }
+
+    /// Gets the number of steps spent contracting per cycle
+    pub fn contraction_period(&self) -> i32 {
+        self.contraction_period
+    }
+
+    /// Gets the number of steps the cycle is shifted forward by
+    pub fn phase_offset(&self) -> i32 {
+        self.phase_offset
+    }
+
+    /// Gets the [Waveform] the muscle follows
+    pub fn waveform(&self) -> &Waveform {
+        &self.waveform
+    }
+
+    /// Gets the number of steps in one full cycle
+    pub fn cycle_length(&self) -> i32 {
+        self.extension_period + self.contraction_period
+    }
+
+    /// Gets the position within the cycle at a step, in steps
+    fn cycle_position(&self, step: i32) -> i32 {
+        (step + self.phase_offset) % self.cycle_length()
+    }
+
     /// Returns true if extending, false if contracting
     pub fn is_extending(&self, step: i32) -> bool {
-        let total = self.extension_period + self.contraction_period;
+        match &self.waveform {
+            Waveform::Keyframes(_) => {
+                self.get_waveform_at(self.cycle_position(step + 1))
+                    >= self.get_waveform_at(self.cycle_position(step))
+            }
+            _ => self.cycle_position(step) < self.extension_period,
+        }
+    }
+
+    /// Gets the value of the waveform at a position within the cycle
+    fn get_waveform_at(&self, step_delta: i32) -> f32 {
+        if let Waveform::Keyframes(keyframes) = &self.waveform {
+            if keyframes.is_empty() {
+                return 0.5;
+            }
+
+            let progress = step_delta as f32 / self.cycle_length() as f32 * keyframes.len() as f32;
+            let index = progress.floor() as usize % keyframes.len();
+            let next_index = (index + 1) % keyframes.len();
+            let t = progress.fract();
+
+            return keyframes[index] + (keyframes[next_index] - keyframes[index]) * t;
+        }
 
-        let step_delta = step % total;
+        // Extension period goes from 0 to 1, contraction period goes from 1 to 0
+        let extension_delta = if step_delta < self.extension_period {
+            step_delta as f32 / self.extension_period as f32
+        } else {
+            1.0 - ((step_delta - self.extension_period) as f32 / self.contraction_period as f32)
+        };
 
-        step_delta < self.extension_period
+        match self.waveform {
+            Waveform::Sine => 0.5 - 0.5 * f32::cos(PI * extension_delta),
+            Waveform::Square => {
+                if step_delta < self.extension_period {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+            _ => extension_delta,
+        }
     }
 
     /// Gets the extension delta based on existing parameters
     /// 0.0 = Fully contracted
     /// 1.0 = Fully extended
     /// 0.5 = Normal
+    ///
+    /// Muscles ease in from normal over the first few steps, so out of phase muscles don't jolt the creature at the start
     pub fn get_extension_at(&self, step: i32) -> f32 {
-        let total = self.extension_period + self.contraction_period;
+        let extension_delta = self.get_waveform_at(self.cycle_position(step));
 
-        let mut step_delta = step % total;
+        if step < WARM_UP_STEPS {
+            let warm_up = step as f32 / WARM_UP_STEPS as f32;
 
-        // Extension period
-        if step_delta < self.extension_period {
-            // First time, go from 0.5 to 1
-            if step < total {
-                return (step_delta as f32 / self.extension_period as f32) * 0.5 + 0.5;
-            }
-
-            // All other times, go from 0 to 1
-            return step_delta as f32 / self.extension_period as f32;
+            return 0.5 + (extension_delta - 0.5) * warm_up;
         }
 
-        step_delta -= self.extension_period;
+        extension_delta
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    pub fn waveforms() {
+        let period = STEPS_PER_SECOND;
+        let quarter = period / 4;
+
+        let sine = MovementParameters::new(1.0, period, period, 0, Waveform::Sine);
+        let square = MovementParameters::new(1.0, period, period, 0, Waveform::Square);
+        let triangle = MovementParameters::new(1.0, period, period, 0, Waveform::Triangle);
+        let keyframes =
+            MovementParameters::new(1.0, period, period, 0, Waveform::Keyframes(vec![0.0, 1.0]));
+
+        assert_eq!(sine.get_extension_at(period * 2 + period), 1.0);
+        assert!((sine.get_extension_at(period * 2 + period / 2) - 0.5).abs() < 0.001);
+        assert_eq!(square.get_extension_at(period * 2 + quarter), 1.0);
+        assert_eq!(square.get_extension_at(period * 3 + quarter), 0.0);
+        assert_eq!(triangle.get_extension_at(period * 2 + quarter), 0.25);
+        assert_eq!(keyframes.get_extension_at(period * 2 + period / 2), 0.5);
+        assert!(keyframes.is_extending(period * 2));
+        assert!(!keyframes.is_extending(period * 3));
+    }
+
+    #[test]
+    pub fn phase_offset() {
+        let period = STEPS_PER_SECOND;
+
+        let in_phase = MovementParameters::new(1.0, period, period, 0, Waveform::Triangle);
+        let out_of_phase = MovementParameters::new(1.0, period, period, period, Waveform::Triangle);
+        let wrapped = MovementParameters::new(1.0, period, period, period * 3, Waveform::Triangle);
+
+        assert_eq!(wrapped.phase_offset(), period);
+
+        for step in period * 2..period * 4 {
+            assert!(
+                (in_phase.get_extension_at(step) + out_of_phase.get_extension_at(step) - 1.0).abs()
+                    < 0.001
+            );
+            assert_ne!(in_phase.is_extending(step), out_of_phase.is_extending(step));
+        }
 
-        // Contraction period (1 -> 0)
-        1.0 - (step_delta as f32 / self.contraction_period as f32)
+        // Everything starts at normal length
+        assert_eq!(out_of_phase.get_extension_at(0), 0.5);
     }
 }
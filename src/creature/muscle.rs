@@ -1,6 +1,10 @@
+use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use crate::util;
+
 /// A muscle, defined by the ids of the two nodes it connects.  Contains a unique id for reference.
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Muscle {
     pub id: Uuid,
     pub from_id: Uuid,
@@ -11,7 +15,7 @@ impl Muscle {
     /// Creates a new muscle from one node to another using their ids
     pub fn new(from_id: Uuid, to_id: Uuid) -> Muscle {
         Muscle {
-            id: Uuid::new_v4(),
+            id: util::new_uuid(),
             from_id,
             to_id,
         }
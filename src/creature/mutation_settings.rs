@@ -0,0 +1,19 @@
+use serde::{Deserialize, Serialize};
+
+/// How often parts of a creature change when it's mutated
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct MutationSettings {
+    /// The chance of each muscle's movement parameters being mutated, where the rest are copied unchanged
+    pub muscle_chance: f32,
+    /// The chance of switching between a timed and a neural controller
+    pub controller_switch_chance: f32,
+}
+
+impl Default for MutationSettings {
+    fn default() -> Self {
+        MutationSettings {
+            muscle_chance: 1.0,
+            controller_switch_chance: 0.02,
+        }
+    }
+}
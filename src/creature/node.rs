@@ -1,8 +1,12 @@
+use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use crate::util;
+
 use super::Position;
 
 /// A node, defined by it's current [Position] and size. Contains a unique id for reference.
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Node {
     pub id: Uuid,
     pub position: Position,
@@ -13,7 +17,7 @@ impl Node {
     /// Creates a new node at a [Position] with a certain size
     pub fn new<N: Into<f32>>(position: Position, size: N) -> Node {
         Node {
-            id: Uuid::new_v4(),
+            id: util::new_uuid(),
             position,
             size: size.into(),
         }
@@ -1,5 +1,7 @@
+use serde::{Deserialize, Serialize};
+
 /// A position in the 2D plane represented by an x and a y
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct Position {
     pub x: f32,
     pub y: f32,
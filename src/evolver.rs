@@ -1,38 +1,128 @@
 //! Manages the evolution of [Creature](crate::creature::Creature)s using [Simulation]s
 
-use std::time::Duration;
+mod settings;
+mod worker;
+
+pub use settings::{
+    EvolverSettings, Selection, EVALUATION_SECONDS_RANGE, GRAVITY_RANGE,
+    OFFSPRING_PER_SURVIVOR_RANGE, POPULATION_RANGE,
+};
+pub use worker::{
+    CreatureSnapshot, EvolverWorker, GenerationStatisticsGuard, Snapshot, Track, TrackedStep,
+    TRACKED_STEPS,
+};
+
+use std::{
+    collections::HashSet,
+    io, mem,
+    path::Path,
+    sync::{Arc, RwLock, RwLockReadGuard},
+    time::{Duration, Instant},
+};
+
+use uuid::Uuid;
 
 use crate::{
-    creature::{CreatureBuilder, Position},
-    simulation::{Simulation, FLOOR_TOP_Y, STEPS_FREQUENCY, STEPS_PER_SECOND, WORLD_X_SIZE},
+    ancestry::Ancestry,
+    creature::{Creature, CreatureBuilder, Position},
+    hall_of_fame::{HallOfFame, HallOfFameEntry},
+    replay::{Replay, REPLAY_STEPS_PER_FRAME},
+    simulation::{
+        Fitness, Simulation, FLOOR_TOP_Y, STEPS_FREQUENCY, STEPS_PER_SECOND, WORLD_X_SIZE,
+    },
+    statistics::{GenerationStatistics, StatisticsExporter, StatisticsFormat},
+    util,
 };
 
-const SIMULATIONS_PER_GENERATION: i32 = 100;
-const STEPS_PER_GENERATION: i32 = STEPS_PER_SECOND * 15;
-const STEPS_PER_EVOLUTION: i32 = STEPS_PER_SECOND * 5;
-// Note that the top (SIMULATIONS_PER_GENERATION / OFFSPRING_PER_CREATURE) simulations will be picked for mutation. MUST BE > 1.
-const OFFSPRING_PER_CREATURE: i32 = 2;
+/// The number of steps each generation is simulated for by default
+pub const STEPS_PER_GENERATION: i32 = STEPS_PER_SECOND * 15;
+/// The number of steps between generations, while the next one is evolved
+pub const STEPS_PER_EVOLUTION: i32 = STEPS_PER_SECOND * 5;
 
 /// Manages the evolution of [Creature](crate::creature::Creature)s using generations of [Simulation]s
 pub struct Evolver {
     current_generation: Vec<Simulation>,
+    /// Made from the current generation while it's evolved, and takes over once evolving ends
+    next_generation: Vec<Simulation>,
     on_generation: usize,
     generation_scores: Vec<Vec<f32>>,
+    generation_fitnesses: Vec<Vec<f32>>,
     time_left_over: Duration,
     state: EvolverState,
+    best_replay: Option<Replay>,
+    generation_started: Instant,
+    /// Shared with [Snapshot]s, which would otherwise have to copy it every generation
+    generation_statistics: Arc<RwLock<Vec<GenerationStatistics>>>,
+    statistics_exporter: Option<StatisticsExporter>,
+    /// Why writing statistics stopped, if it did
+    statistics_error: Option<io::Error>,
+    templates: Vec<Creature>,
+    settings: EvolverSettings,
+    /// Shared with [Snapshot]s, like the statistics
+    ancestry: Arc<RwLock<Ancestry>>,
+    run_id: Uuid,
+    seed: u64,
+    hall_of_fame: Option<HallOfFame>,
+    /// Why the hall of fame last failed to save, until it saves again
+    hall_of_fame_error: Option<io::Error>,
 }
 
 impl Evolver {
-    /// Creates a new Evolver
+    /// Creates a new Evolver, starting from random creatures
     pub fn new() -> Evolver {
+        Self::with_settings(EvolverSettings::default(), Vec::new())
+    }
+
+    /// Creates a new Evolver whose first generation is made up of the template creatures, mutants of them
+    /// and a number of random immigrants. Without any templates, the first generation is entirely random
+    pub fn with_templates(templates: Vec<Creature>, random_immigrants: usize) -> Evolver {
+        let settings = EvolverSettings {
+            random_immigrants,
+            ..Default::default()
+        };
+
+        Self::with_settings(settings, templates)
+    }
+
+    /// Creates a new Evolver that runs with [EvolverSettings], starting from templates like
+    /// [Evolver::with_templates]. The settings should be validated first. Templates sharing an id, like the
+    /// same creature loaded twice, are only used once so every creature of a generation has its own id.
+    ///
+    /// This seeds the current thread's random number generator, see [util::seed_rng]. Without a seed in the
+    /// settings, a random one is picked so the run can still be repeated
+    pub fn with_settings(settings: EvolverSettings, mut templates: Vec<Creature>) -> Evolver {
+        let mut template_ids = HashSet::new();
+
+        templates.retain(|template| template_ids.insert(*template.id()));
+
+        // Picked before seeding, so runs with the same seed are still told apart
+        let run_id = uuid::Builder::from_random_bytes(rand::random()).into_uuid();
+        let seed = settings.seed.unwrap_or_else(rand::random);
+
+        util::seed_rng(Some(seed));
+
         let mut evolver = Evolver {
             current_generation: Vec::new(),
+            next_generation: Vec::new(),
             on_generation: 0,
             generation_scores: Vec::new(),
+            generation_fitnesses: Vec::new(),
             time_left_over: Duration::ZERO,
             state: EvolverState::SimulatingGeneration {
-                steps_left: STEPS_PER_GENERATION,
+                steps_left: settings.steps_per_generation(),
             },
+            best_replay: None,
+            generation_started: Instant::now(),
+            generation_statistics: Arc::new(RwLock::new(Vec::new())),
+            statistics_exporter: None,
+            statistics_error: None,
+            templates,
+            settings,
+            ancestry: Arc::new(RwLock::new(Ancestry::new())),
+            run_id,
+            seed,
+            hall_of_fame: None,
+            hall_of_fame_error: None,
         };
 
         evolver.generate_next_generation();
@@ -40,59 +130,292 @@ impl Evolver {
         evolver
     }
 
+    /// Enters the best creature of every generation into a [HallOfFame] from now on
+    pub fn with_hall_of_fame(mut self, hall_of_fame: HallOfFame) -> Evolver {
+        self.hall_of_fame = Some(hall_of_fame);
+        self
+    }
+
+    /// Gets the [HallOfFame] the best creatures are entered into, if there is one
+    pub fn hall_of_fame(&self) -> Option<&HallOfFame> {
+        self.hall_of_fame.as_ref()
+    }
+
+    /// Saves anything entered into the [HallOfFame] that hasn't been saved yet
+    pub fn save_hall_of_fame(&mut self) {
+        if let Some(hall_of_fame) = &mut self.hall_of_fame {
+            if hall_of_fame.is_unsaved() {
+                self.hall_of_fame_error = hall_of_fame.save().err();
+            }
+        }
+    }
+
+    /// Gets the error the hall of fame last failed to save with, if it hasn't saved since
+    pub fn hall_of_fame_error(&self) -> Option<&io::Error> {
+        self.hall_of_fame_error.as_ref()
+    }
+
+    /// Gets the seed the run started from, which was picked at random if the settings didn't have one
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
     /// Gets the current state of the Evolver
     pub fn state(&self) -> EvolverState {
         self.state
     }
 
-    /// Generates the next generation from the current one or randomly if the first generation
-    fn generate_next_generation(&mut self) {
+    /// Gets the settings the Evolver runs with
+    pub fn settings(&self) -> &EvolverSettings {
+        &self.settings
+    }
+
+    /// Gets what creatures are selected by
+    pub fn fitness(&self) -> Fitness {
+        self.settings.fitness
+    }
+
+    /// Sets what creatures are selected by, starting from the next evolution
+    pub fn set_fitness(&mut self, fitness: Fitness) {
+        self.settings.fitness = fitness;
+    }
+
+    /// Gets a [Replay] of the best creature of the last generation, if there has been one
+    pub fn best_replay(&self) -> Option<&Replay> {
+        self.best_replay.as_ref()
+    }
+
+    /// Creates a [Simulation] of a creature born in a generation
+    fn new_simulation(&self, builder: CreatureBuilder, birth_generation: usize) -> Simulation {
         let bottom_center = Position::new(WORLD_X_SIZE / 2.0, FLOOR_TOP_Y);
+
+        Simulation::with_environment(
+            builder
+                .add_birth_generation(birth_generation)
+                .translate_bottom_center_to(&bottom_center)
+                .build(),
+            self.settings.environment,
+        )
+    }
+
+    /// Simulates a creature again from the start while recording it, up to where a simulation of it got to.
+    /// Simulations are deterministic, so only the creature worth replaying has to be recorded
+    fn rerun_recording(&self, simulation: &Simulation) -> Simulation {
+        let mut rerun =
+            Simulation::with_environment(simulation.creature().clone(), self.settings.environment);
+
+        rerun.start_recording(REPLAY_STEPS_PER_FRAME);
+
+        while rerun.steps() < simulation.steps() {
+            rerun.step();
+        }
+
+        rerun
+    }
+
+    /// Generates the next generation from the current one, which takes over once evolving ends, or starts the
+    /// first generation
+    fn generate_next_generation(&mut self) {
+        let birth_generation = self.on_generation + 1;
+
         if self.on_generation == 0 {
-            // Create first generation
+            // Create first generation from the templates, their mutants and random immigrants
+            let population = self.settings.population;
             let mut generation = Vec::new();
+            let mutants_end = population.saturating_sub(self.settings.random_immigrants);
 
-            for _ in 0..SIMULATIONS_PER_GENERATION {
-                generation.push(Simulation::new(
-                    CreatureBuilder::random()
-                        .translate_bottom_center_to(&bottom_center)
-                        .build(),
-                ))
+            for template in self.templates.iter().take(population) {
+                generation
+                    .push(self.new_simulation(CreatureBuilder::copy(template), birth_generation));
+            }
+
+            for template in self.templates.iter().cycle() {
+                if generation.len() >= mutants_end {
+                    break;
+                }
+
+                generation.push(self.new_simulation(
+                    CreatureBuilder::mutate_with(template, self.settings.mutation),
+                    birth_generation,
+                ));
+            }
+
+            while generation.len() < population {
+                generation.push(self.new_simulation(CreatureBuilder::random(), birth_generation))
             }
 
             self.current_generation = generation;
+            self.record_ancestry();
             self.on_generation += 1;
 
             return;
         }
 
         // Otherwise, improve last generation
-        let sorted_generation = &mut self.current_generation;
-        sorted_generation.sort_by(|a, b| b.get_score().total_cmp(&a.get_score()));
+        let fitness = self.settings.fitness;
+        let fitnesses: Vec<f32> = self
+            .current_generation
+            .iter()
+            .map(|s| s.get_fitness(fitness))
+            .collect();
+        let selection = self.settings.select(&fitnesses);
+
+        // Ranked from the fittest, with scores staying distances whatever the fitness so they can be compared
+        // across runs
+        let old_scores: Vec<f32> = selection
+            .ranking
+            .iter()
+            .map(|index| self.current_generation[*index].get_score())
+            .collect();
+        let old_fitnesses: Vec<f32> = selection
+            .ranking
+            .iter()
+            .map(|index| fitnesses[*index])
+            .collect();
+        self.record_statistics(&old_scores);
+
+        let mut ancestry = util::write(&self.ancestry);
+
+        for (index, score) in selection.ranking.iter().zip(&old_scores) {
+            ancestry.set_score(*self.current_generation[*index].creature().id(), *score);
+        }
+
+        drop(ancestry);
 
-        let old_scores = sorted_generation.iter().map(|s| s.get_score()).collect();
         self.generation_scores.push(old_scores);
+        self.generation_fitnesses.push(old_fitnesses);
+
+        let generation = &self.current_generation;
+        let best = selection.ranking.first().map(|index| &generation[*index]);
+        self.best_replay = best.and_then(|best| self.rerun_recording(best).replay());
 
-        let mut new_generation = Vec::new();
+        if let (Some(hall_of_fame), Some(best)) = (&mut self.hall_of_fame, best) {
+            let entry = HallOfFameEntry::new(
+                best.creature().clone(),
+                best.get_score(),
+                best.get_fitness(fitness),
+                self.on_generation,
+                self.run_id,
+                self.seed,
+                self.settings.clone(),
+            );
 
-        for simulation in sorted_generation.iter() {
-            if new_generation.len() as i32 >= SIMULATIONS_PER_GENERATION {
-                break;
+            match hall_of_fame.record(entry) {
+                Err(error) => self.hall_of_fame_error = Some(error),
+                // Saves are spaced out, so an entry may not have been saved yet
+                Ok(()) if !hall_of_fame.is_unsaved() => self.hall_of_fame_error = None,
+                Ok(()) => {}
             }
+        }
 
-            let old_creature = simulation.creature();
+        let new_generation = selection
+            .parents
+            .iter()
+            .map(|parent| {
+                self.new_simulation(
+                    CreatureBuilder::mutate_with(
+                        generation[*parent].creature(),
+                        self.settings.mutation,
+                    ),
+                    birth_generation,
+                )
+            })
+            .collect();
 
-            for _ in 0..OFFSPRING_PER_CREATURE {
-                let builder = CreatureBuilder::mutate(old_creature);
+        self.next_generation = new_generation;
+    }
 
-                new_generation.push(Simulation::new(
-                    builder.translate_bottom_center_to(&bottom_center).build(),
-                ));
+    /// Replaces the current generation with the one generated from it
+    fn start_next_generation(&mut self) {
+        self.current_generation = mem::take(&mut self.next_generation);
+        self.record_ancestry();
+        self.on_generation += 1;
+        self.generation_started = Instant::now();
+    }
+
+    /// Records every creature of the current generation in the ancestry
+    fn record_ancestry(&mut self) {
+        let mut ancestry = util::write(&self.ancestry);
+
+        for simulation in &self.current_generation {
+            ancestry.record(simulation.creature());
+        }
+    }
+
+    /// Gets every creature of the run so far and who they came from. The evolver can't record the next
+    /// generation until this is dropped
+    pub fn ancestry(&self) -> RwLockReadGuard<'_, Ancestry> {
+        util::read(&self.ancestry)
+    }
+
+    /// Summarizes the current generation and writes it to the statistics file, if exporting
+    fn record_statistics(&mut self, scores: &[f32]) {
+        if scores.is_empty() {
+            return;
+        }
+
+        let count = self.current_generation.len() as f32;
+        let average_nodes = self
+            .current_generation
+            .iter()
+            .map(|simulation| simulation.creature().nodes().len() as f32)
+            .sum::<f32>()
+            / count;
+        let average_muscles = self
+            .current_generation
+            .iter()
+            .map(|simulation| simulation.creature().muscles().len() as f32)
+            .sum::<f32>()
+            / count;
+
+        let statistics = GenerationStatistics::new(
+            self.on_generation,
+            scores,
+            average_nodes,
+            average_muscles,
+            self.generation_started.elapsed(),
+        );
+
+        if let Some(exporter) = &mut self.statistics_exporter {
+            if let Err(error) = exporter.write(&statistics) {
+                self.statistics_exporter = None;
+                self.statistics_error = Some(error);
             }
         }
 
-        self.current_generation = new_generation;
-        self.on_generation += 1;
+        util::write(&self.generation_statistics).push(statistics);
+    }
+
+    /// Gets the statistics of past generations' scores (distances). The evolver can't record the next
+    /// generation until this is dropped
+    pub fn generation_statistics(&self) -> RwLockReadGuard<'_, Vec<GenerationStatistics>> {
+        util::read(&self.generation_statistics)
+    }
+
+    /// Starts writing the statistics of every generation to a file, as CSV or JSON Lines depending on its
+    /// extension (see [StatisticsFormat::from_path]). Past generations are written immediately
+    pub fn export_statistics_to(&mut self, path: &Path) -> io::Result<()> {
+        self.statistics_error = None;
+
+        let mut exporter = StatisticsExporter::create(path, StatisticsFormat::from_path(path))?;
+
+        for statistics in self.generation_statistics().iter() {
+            exporter.write(statistics)?;
+        }
+
+        self.statistics_exporter = Some(exporter);
+
+        Ok(())
+    }
+
+    /// Returns true if writing statistics to a file
+    pub fn is_exporting_statistics(&self) -> bool {
+        self.statistics_exporter.is_some()
+    }
+
+    /// Gets the error that stopped statistics from being written, if one did
+    pub fn statistics_error(&self) -> Option<&io::Error> {
+        self.statistics_error.as_ref()
     }
 
     /// Gets the current generation
@@ -100,18 +423,30 @@ impl Evolver {
         &self.current_generation
     }
 
+    /// Gets the offspring of the current generation while it's evolved, in the order their parents were picked.
+    /// It's empty the rest of the time
+    pub fn next_generation(&self) -> &[Simulation] {
+        &self.next_generation
+    }
+
     /// Returns the number of the current generation
     pub fn on_generation(&self) -> usize {
         self.on_generation
     }
 
-    /// Gets the stored scores for past generations
+    /// Gets the stored scores (distances) for past generations, best fitness first
     pub fn generation_scores(&self) -> &Vec<Vec<f32>> {
         &self.generation_scores
     }
 
-    /// Steps the evolver
-    fn step(&mut self) {
+    /// Gets the stored fitnesses for past generations, best first, as measured by the [Fitness] used when each
+    /// was evolved
+    pub fn generation_fitnesses(&self) -> &Vec<Vec<f32>> {
+        &self.generation_fitnesses
+    }
+
+    /// Steps the evolver by one physics step
+    pub fn step(&mut self) {
         match self.state {
             EvolverState::SimulatingGeneration { ref mut steps_left } => {
                 *steps_left -= 1;
@@ -119,6 +454,7 @@ impl Evolver {
                     self.state = EvolverState::Evolving {
                         steps_left: STEPS_PER_EVOLUTION,
                     };
+                    self.generate_next_generation();
 
                     return;
                 }
@@ -130,10 +466,10 @@ impl Evolver {
                 *steps_left -= 1;
                 if *steps_left <= 0 {
                     self.state = EvolverState::SimulatingGeneration {
-                        steps_left: STEPS_PER_GENERATION,
+                        steps_left: self.settings.steps_per_generation(),
                     };
 
-                    self.generate_next_generation();
+                    self.start_next_generation();
                 }
             }
         };
@@ -150,6 +486,15 @@ impl Evolver {
 
         self.time_left_over = time;
     }
+
+    /// Steps the evolver until the next generation starts
+    pub fn finish_generation(&mut self) {
+        let generation = self.on_generation;
+
+        while self.on_generation == generation {
+            self.step();
+        }
+    }
 }
 
 impl Default for Evolver {
@@ -165,3 +510,259 @@ pub enum EvolverState {
     SimulatingGeneration { steps_left: i32 },
     Evolving { steps_left: i32 },
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use uuid::Uuid;
+
+    use super::*;
+
+    #[test]
+    pub fn templates() {
+        let first = CreatureBuilder::random().build();
+        let second = CreatureBuilder::random().build();
+        let template_ids = [*first.id(), *second.id()];
+        // The same creature twice only counts once
+        let templates = vec![first.clone(), second, first];
+        let evolver = Evolver::with_templates(templates, 10);
+        let generation = evolver.current_generation();
+
+        let copies = generation
+            .iter()
+            .filter(|simulation| template_ids.contains(simulation.creature().id()))
+            .count();
+        let mutants = generation
+            .iter()
+            .filter(|simulation| {
+                simulation
+                    .creature()
+                    .parent_id()
+                    .map_or(false, |parent_id| template_ids.contains(parent_id))
+            })
+            .count();
+
+        let population = EvolverSettings::default().population;
+
+        assert_eq!(generation.len(), population);
+        assert_eq!(copies, 2);
+        assert_eq!(mutants, population - 2 - 10);
+        assert_eq!(evolver.ancestry().len(), population);
+    }
+
+    #[test]
+    pub fn settings() {
+        let settings = EvolverSettings {
+            population: 11,
+            evaluation_seconds: 1.0,
+            offspring_per_survivor: 3,
+            seed: Some(42),
+            ..Default::default()
+        };
+        let ids = |evolver: &Evolver| -> Vec<Uuid> {
+            evolver
+                .current_generation()
+                .iter()
+                .map(|simulation| *simulation.creature().id())
+                .collect()
+        };
+
+        assert!(settings.validate().is_ok());
+        assert!(EvolverSettings {
+            offspring_per_survivor: 12,
+            ..settings.clone()
+        }
+        .validate()
+        .is_err());
+
+        // The same seed makes the same creatures
+        let mut evolver = Evolver::with_settings(settings.clone(), Vec::new());
+
+        assert_eq!(
+            ids(&evolver),
+            ids(&Evolver::with_settings(settings.clone(), Vec::new()))
+        );
+
+        let first_generation = ids(&evolver);
+
+        evolver.finish_generation();
+
+        // Only the best 11 / 3 = 3 have offspring, however the population divides
+        let parents: HashSet<Uuid> = evolver
+            .current_generation()
+            .iter()
+            .filter_map(|simulation| simulation.creature().parent_id().copied())
+            .collect();
+
+        assert_eq!(evolver.current_generation().len(), 11);
+        assert_eq!(parents.len(), settings.survivors());
+        assert!(parents.iter().all(|id| first_generation.contains(id)));
+    }
+
+    #[test]
+    pub fn selection() {
+        let settings = EvolverSettings {
+            population: 7,
+            offspring_per_survivor: 3,
+            ..Default::default()
+        };
+
+        // Ties go to whoever comes first
+        let selection = settings.select(&[1.0, 5.0, 3.0, 5.0, 0.0, 2.0, 4.0]);
+
+        assert_eq!(selection.ranking, vec![1, 3, 6, 2, 5, 0, 4]);
+        assert_eq!(selection.survivors, vec![1, 3]);
+        assert_eq!(selection.parents, vec![1, 1, 1, 3, 3, 3, 1]);
+    }
+
+    #[test]
+    pub fn efficiency() {
+        let settings = EvolverSettings {
+            population: 10,
+            evaluation_seconds: 2.0,
+            seed: Some(7),
+            fitness: Fitness::Efficiency,
+            ..Default::default()
+        };
+        let mut evolver = Evolver::with_settings(settings, Vec::new());
+
+        evolver.finish_generation();
+
+        // Ranked by efficiency, while the scores stay distances
+        let fitnesses = &evolver.generation_fitnesses()[0];
+        let scores = &evolver.generation_scores()[0];
+
+        assert!(fitnesses.windows(2).all(|pair| pair[0] >= pair[1]));
+        assert!(fitnesses.iter().all(|fitness| *fitness >= 0.0));
+        assert_eq!(fitnesses.len(), 10);
+        assert!(fitnesses
+            .iter()
+            .zip(scores)
+            .all(|(fitness, score)| *fitness <= score.max(0.0)));
+    }
+
+    #[test]
+    pub fn next_generation() {
+        let settings = EvolverSettings {
+            population: 6,
+            evaluation_seconds: 1.0,
+            seed: Some(9),
+            ..Default::default()
+        };
+        let mut evolver = Evolver::with_settings(settings.clone(), Vec::new());
+
+        assert!(evolver.next_generation().is_empty());
+
+        while matches!(evolver.state(), EvolverState::SimulatingGeneration { .. }) {
+            evolver.step();
+        }
+
+        // Made as soon as the generation is evolved, so it can be shown being made
+        let parents: HashSet<Uuid> = evolver
+            .current_generation()
+            .iter()
+            .map(|simulation| *simulation.creature().id())
+            .collect();
+        let offspring: Vec<Uuid> = evolver
+            .next_generation()
+            .iter()
+            .map(|simulation| *simulation.creature().id())
+            .collect();
+
+        assert_eq!(offspring.len(), settings.population);
+        assert!(evolver
+            .next_generation()
+            .iter()
+            .all(|simulation| parents.contains(simulation.creature().parent_id().unwrap())));
+
+        evolver.finish_generation();
+
+        let current: Vec<Uuid> = evolver
+            .current_generation()
+            .iter()
+            .map(|simulation| *simulation.creature().id())
+            .collect();
+
+        assert_eq!(current, offspring);
+        assert!(evolver.next_generation().is_empty());
+    }
+
+    #[test]
+    pub fn best_replay() {
+        let settings = EvolverSettings {
+            population: 5,
+            evaluation_seconds: 1.0,
+            seed: Some(3),
+            ..Default::default()
+        };
+        let mut evolver = Evolver::with_settings(settings, Vec::new());
+
+        for _ in 0..STEPS_PER_SECOND / 2 {
+            evolver.step();
+        }
+
+        // Rerunning a creature ends up exactly where it got to the first time
+        let simulation = &evolver.current_generation()[0];
+        let rerun = evolver.rerun_recording(simulation);
+
+        assert_eq!(rerun.steps(), simulation.steps());
+        assert_eq!(rerun.get_score(), simulation.get_score());
+        assert_eq!(rerun.get_total_energy(), simulation.get_total_energy());
+        assert!(simulation.replay().is_none());
+
+        let first_generation: Vec<Uuid> = evolver
+            .current_generation()
+            .iter()
+            .map(|simulation| *simulation.creature().id())
+            .collect();
+
+        evolver.finish_generation();
+
+        let best = evolver.best_replay().unwrap();
+
+        assert!(first_generation.contains(best.creature().id()));
+        assert!(best.frame_count() > 1);
+    }
+    /// A file that can't be written to, like a full disk
+    #[cfg(unix)]
+    #[test]
+    pub fn statistics_error() {
+        let directory = util::TestDirectory::new("statistics-error");
+        let path = directory.path().join("statistics.jsonl");
+        let settings = EvolverSettings {
+            population: 4,
+            evaluation_seconds: 1.0,
+            seed: Some(5),
+            ..Default::default()
+        };
+        let mut evolver = Evolver::with_settings(settings, Vec::new());
+
+        std::os::unix::fs::symlink("/dev/full", &path).unwrap();
+        evolver.export_statistics_to(&path).unwrap();
+        evolver.finish_generation();
+
+        assert!(!evolver.is_exporting_statistics());
+        assert!(evolver.statistics_error().is_some());
+    }
+    #[test]
+    pub fn hall_of_fame_error() {
+        let directory = util::TestDirectory::new("hall-of-fame-error");
+        let path = directory.path().join("hall_of_fame.json");
+        let settings = EvolverSettings {
+            population: 4,
+            evaluation_seconds: 1.0,
+            seed: Some(5),
+            ..Default::default()
+        };
+        let hall_of_fame = HallOfFame::load(&path).unwrap();
+        let mut evolver =
+            Evolver::with_settings(settings, Vec::new()).with_hall_of_fame(hall_of_fame);
+
+        // Taken by a directory, so the file can't be written
+        std::fs::create_dir(&path).unwrap();
+        evolver.finish_generation();
+
+        assert!(evolver.hall_of_fame_error().is_some());
+    }
+}
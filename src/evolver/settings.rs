@@ -0,0 +1,165 @@
+use std::ops::RangeInclusive;
+
+use serde::{Deserialize, Serialize};
+
+use super::STEPS_PER_GENERATION;
+use crate::{
+    creature::MutationSettings,
+    simulation::{Environment, Fitness, STEPS_PER_SECOND},
+};
+
+/// The allowed number of creatures in a generation
+pub const POPULATION_RANGE: RangeInclusive<usize> = 2..=1000;
+/// The allowed time each generation is simulated for, in seconds
+pub const EVALUATION_SECONDS_RANGE: RangeInclusive<f32> = 1.0..=120.0;
+/// The allowed number of offspring per surviving creature
+pub const OFFSPRING_PER_SURVIVOR_RANGE: RangeInclusive<usize> = 2..=20;
+/// The allowed strength of gravity
+pub const GRAVITY_RANGE: RangeInclusive<f32> = 0.0..=1000.0;
+
+const CHANCE_RANGE: RangeInclusive<f32> = 0.0..=1.0;
+
+/// Everything about how an [Evolver](super::Evolver) runs that can be chosen before it starts
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EvolverSettings {
+    /// How many creatures are in each generation
+    pub population: usize,
+    /// How long each generation is simulated for, in seconds
+    pub evaluation_seconds: f32,
+    /// What creatures are selected by
+    pub fitness: Fitness,
+    /// How many offspring each surviving creature has, which decides how many survive
+    pub offspring_per_survivor: usize,
+    /// How likely offspring are to differ from their parent
+    pub mutation: MutationSettings,
+    /// The world creatures are simulated in
+    pub environment: Environment,
+    /// The seed everything random comes from, or none for a different run every time
+    pub seed: Option<u64>,
+    /// How many random creatures join the first generation when starting from templates
+    pub random_immigrants: usize,
+}
+
+impl EvolverSettings {
+    /// Gets how many steps each generation is simulated for
+    pub fn steps_per_generation(&self) -> i32 {
+        (self.evaluation_seconds * STEPS_PER_SECOND as f32).round() as i32
+    }
+
+    /// Gets how many of the best creatures of a generation have offspring
+    pub fn survivors(&self) -> usize {
+        (self.population / self.offspring_per_survivor.max(1)).max(1)
+    }
+
+    /// Gets which survivor, counting from the best, is the parent of an offspring in the next generation. The
+    /// best have offspring in order, going back to the best if the population doesn't divide evenly
+    pub fn parent_index(&self, offspring: usize, survivors: usize) -> usize {
+        (offspring / self.offspring_per_survivor.max(1)) % survivors
+    }
+
+    /// Selects the survivors of a generation and the parent of each offspring, from the fitness of each of its
+    /// creatures in order
+    pub fn select(&self, fitnesses: &[f32]) -> Selection {
+        // Sorted stably, so ties go to whoever comes first
+        let mut ranking: Vec<usize> = (0..fitnesses.len()).collect();
+        ranking.sort_by(|a, b| fitnesses[*b].total_cmp(&fitnesses[*a]));
+
+        let survivors = ranking[..self.survivors().min(ranking.len())].to_vec();
+        let parents = if survivors.is_empty() {
+            Vec::new()
+        } else {
+            (0..self.population)
+                .map(|offspring| survivors[self.parent_index(offspring, survivors.len())])
+                .collect()
+        };
+
+        Selection {
+            ranking,
+            survivors,
+            parents,
+        }
+    }
+
+    /// Checks the settings can be run, describing the first problem if not
+    pub fn validate(&self) -> Result<(), String> {
+        if !POPULATION_RANGE.contains(&self.population) {
+            return Err(format!(
+                "The population must be between {} and {}",
+                POPULATION_RANGE.start(),
+                POPULATION_RANGE.end()
+            ));
+        }
+
+        if !EVALUATION_SECONDS_RANGE.contains(&self.evaluation_seconds) {
+            return Err(format!(
+                "The evaluation time must be between {}s and {}s",
+                EVALUATION_SECONDS_RANGE.start(),
+                EVALUATION_SECONDS_RANGE.end()
+            ));
+        }
+
+        if !OFFSPRING_PER_SURVIVOR_RANGE.contains(&self.offspring_per_survivor) {
+            return Err(format!(
+                "The offspring per survivor must be between {} and {}",
+                OFFSPRING_PER_SURVIVOR_RANGE.start(),
+                OFFSPRING_PER_SURVIVOR_RANGE.end()
+            ));
+        }
+
+        if self.offspring_per_survivor > self.population {
+            return Err(
+                "There must be at least as many creatures as offspring per survivor".into(),
+            );
+        }
+
+        if !CHANCE_RANGE.contains(&self.mutation.muscle_chance)
+            || !CHANCE_RANGE.contains(&self.mutation.controller_switch_chance)
+        {
+            return Err("Mutation chances must be between 0 and 1".into());
+        }
+
+        if !GRAVITY_RANGE.contains(&self.environment.gravity) {
+            return Err(format!(
+                "Gravity must be between {} and {}",
+                GRAVITY_RANGE.start(),
+                GRAVITY_RANGE.end()
+            ));
+        }
+
+        if self.random_immigrants > self.population {
+            return Err("There can't be more random immigrants than creatures".into());
+        }
+
+        Ok(())
+    }
+}
+
+/// Which creatures of a generation survive and which of them is the parent of each offspring, see
+/// [EvolverSettings::select]
+#[derive(Debug, PartialEq, Eq)]
+pub struct Selection {
+    /// Indices into the generation, from the fittest to the least fit
+    pub ranking: Vec<usize>,
+    /// Indices into the generation, best first
+    pub survivors: Vec<usize>,
+    /// Indices into the generation, for each offspring of the next generation in order
+    pub parents: Vec<usize>,
+}
+
+impl Default for EvolverSettings {
+    /// The classic run: 100 random creatures simulated on flat ground for 15 seconds, with the best half having
+    /// two offspring each
+    fn default() -> Self {
+        EvolverSettings {
+            population: 100,
+            evaluation_seconds: (STEPS_PER_GENERATION / STEPS_PER_SECOND) as f32,
+            fitness: Fitness::default(),
+            offspring_per_survivor: 2,
+            mutation: MutationSettings::default(),
+            environment: Environment::default(),
+            seed: None,
+            random_immigrants: 0,
+        }
+    }
+}
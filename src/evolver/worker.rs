@@ -0,0 +1,847 @@
+//! Runs an [Evolver] on its own thread, so simulation and rendering never hold each other up
+
+use std::{
+    collections::VecDeque,
+    ops::Deref,
+    path::PathBuf,
+    sync::{
+        mpsc::{self, Receiver, Sender, SyncSender, TryRecvError, TrySendError},
+        Arc, RwLock, RwLockReadGuard,
+    },
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+use rapier::prelude::Vector;
+use uuid::Uuid;
+
+use super::{Evolver, EvolverSettings, EvolverState};
+use crate::{
+    ancestry::Ancestry,
+    creature::Creature,
+    replay::Replay,
+    simulation::{Simulation, SimulationView, Terrain, STEPS_FREQUENCY, STEPS_PER_SECOND},
+    statistics::GenerationStatistics,
+    util,
+};
+
+const SNAPSHOT_INTERVAL: Duration = Duration::from_millis(15); // The least time between published snapshots
+const TURBO_SNAPSHOT_INTERVAL: Duration = Duration::from_millis(100); // Nothing is animated in turbo
+const MAX_BATCH_TIME: Duration = Duration::from_millis(15); // How long to step before checking for commands
+const MAX_BACKLOG: Duration = Duration::from_millis(250); // Real time the simulation can fall behind by
+const IDLE_SLEEP: Duration = Duration::from_millis(1);
+
+pub const TRACKED_STEPS: i32 = STEPS_PER_SECOND * 5; // How far back a tracked creature's movement is kept
+
+/// Something the UI asks the worker to do
+enum Command {
+    SetSpeed(f32),
+    SetPaused(bool),
+    SetTurbo(bool),
+    Step,
+    FinishGeneration,
+    ExportStatistics(PathBuf),
+    Track(Option<Uuid>),
+    Stop,
+}
+
+/// A handle to an [Evolver] running on its own thread, which is controlled by commands and publishes
+/// [Snapshot]s of itself. Dropping the handle stops the thread and waits for it to save what it has to
+pub struct EvolverWorker {
+    commands: Sender<Command>,
+    snapshots: Receiver<Snapshot>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl EvolverWorker {
+    /// Starts running the evolver made by `create` at normal speed. It's made on the worker's thread, so a
+    /// seed it sets applies to everything random about the run
+    pub fn spawn(create: impl FnOnce() -> Evolver + Send + 'static) -> EvolverWorker {
+        let (commands, command_receiver) = mpsc::channel();
+        // Only one snapshot waits at a time, so the UI never falls behind
+        let (snapshot_sender, snapshots) = mpsc::sync_channel(1);
+
+        let thread = thread::Builder::new()
+            .name("evolver".to_owned())
+            .spawn(move || work(create(), command_receiver, snapshot_sender))
+            .expect("Failed to start the evolver thread");
+
+        EvolverWorker {
+            commands,
+            snapshots,
+            thread: Some(thread),
+        }
+    }
+
+    /// Sets how many times faster than real time the evolver runs
+    pub fn set_speed(&self, speed: f32) {
+        self.send(Command::SetSpeed(speed));
+    }
+
+    /// Pauses or resumes the evolver
+    pub fn set_paused(&self, paused: bool) {
+        self.send(Command::SetPaused(paused));
+    }
+
+    /// Turns turbo on or off. In turbo the evolver ignores the speed and runs as fast as it can
+    pub fn set_turbo(&self, turbo: bool) {
+        self.send(Command::SetTurbo(turbo));
+    }
+
+    /// Runs a single physics step, see [Evolver::step]
+    pub fn step(&self) {
+        self.send(Command::Step);
+    }
+
+    /// Runs until the next generation starts, see [Evolver::finish_generation]
+    pub fn finish_generation(&self) {
+        self.send(Command::FinishGeneration);
+    }
+
+    /// Starts writing statistics to a file, see [Evolver::export_statistics_to]. The outcome is reported by
+    /// [Snapshot::statistics_export]
+    pub fn export_statistics_to(&self, path: PathBuf) {
+        self.send(Command::ExportStatistics(path));
+    }
+
+    /// Starts recording every step of a creature of the current generation, or stops if there's none. The
+    /// recent steps are published as [Snapshot::track]
+    pub fn track(&self, creature: Option<Uuid>) {
+        self.send(Command::Track(creature));
+    }
+
+    /// Takes the newest snapshot published since the last one was taken, without waiting
+    pub fn latest_snapshot(&self) -> Option<Snapshot> {
+        self.snapshots.try_iter().last()
+    }
+
+    /// Waits up to a timeout for the next snapshot to be published
+    pub fn wait_for_snapshot(&self, timeout: Duration) -> Option<Snapshot> {
+        self.snapshots.recv_timeout(timeout).ok()
+    }
+
+    fn send(&self, command: Command) {
+        // Only fails if the worker has panicked, which has already been reported
+        let _ = self.commands.send(command);
+    }
+}
+
+impl Drop for EvolverWorker {
+    fn drop(&mut self) {
+        self.send(Command::Stop);
+
+        if let Some(thread) = self.thread.take() {
+            // A panic has already been reported, and there's nothing left to save
+            let _ = thread.join();
+        }
+    }
+}
+
+/// Runs the evolver in real time scaled by the speed, or flat out in turbo, until the [EvolverWorker] is dropped.
+/// The hall of fame is saved before stopping
+fn work(mut evolver: Evolver, commands: Receiver<Command>, snapshots: SyncSender<Snapshot>) {
+    let mut speed = 1.0;
+    let mut paused = false;
+    let mut turbo = false;
+    let mut time_owed = Duration::ZERO;
+    let mut last_tick = Instant::now();
+    let mut last_published: Option<Instant> = None;
+    let mut changed = true;
+    let mut shared = SharedState::new(&evolver);
+    let mut statistics_export = None;
+    let mut tracker = Tracker::default();
+
+    'run: loop {
+        loop {
+            let command = match commands.try_recv() {
+                Ok(command) => command,
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => break 'run,
+            };
+
+            // The UI sends its transport settings every frame, which only count as a change if they are one
+            match command {
+                Command::SetSpeed(new_speed) if new_speed == speed => continue,
+                Command::SetPaused(new_paused) if new_paused == paused => continue,
+                Command::SetTurbo(new_turbo) if new_turbo == turbo => continue,
+                Command::SetSpeed(new_speed) => speed = new_speed,
+                Command::SetPaused(new_paused) => paused = new_paused,
+                Command::SetTurbo(new_turbo) => {
+                    turbo = new_turbo;
+                    time_owed = Duration::ZERO;
+                }
+                Command::Step => {
+                    evolver.step();
+                    tracker.record(&evolver);
+                }
+                Command::FinishGeneration => {
+                    evolver.finish_generation();
+                    tracker.record(&evolver);
+                }
+                Command::ExportStatistics(path) => {
+                    statistics_export = Some(
+                        evolver
+                            .export_statistics_to(&path)
+                            .map(|_| path)
+                            .map_err(|error| error.to_string()),
+                    );
+                }
+                Command::Track(creature) => {
+                    tracker.follow(creature);
+                    // So there's something to show straight away, even while paused
+                    tracker.record(&evolver);
+                }
+                Command::Stop => break 'run,
+            }
+
+            changed = true;
+        }
+
+        let now = Instant::now();
+
+        let running_flat_out = turbo && !paused;
+
+        if !paused && !turbo {
+            time_owed += now.duration_since(last_tick).mul_f32(speed);
+        }
+
+        last_tick = now;
+
+        while (running_flat_out || time_owed >= STEPS_FREQUENCY) && now.elapsed() < MAX_BATCH_TIME {
+            evolver.step();
+            tracker.record(&evolver);
+            time_owed = time_owed.saturating_sub(STEPS_FREQUENCY);
+            changed = true;
+        }
+
+        // Drops time it can't catch up on, rather than falling further behind
+        time_owed = time_owed.min(MAX_BACKLOG.mul_f32(speed));
+
+        let snapshot_interval = if turbo {
+            TURBO_SNAPSHOT_INTERVAL
+        } else {
+            SNAPSHOT_INTERVAL
+        };
+
+        if changed && last_published.map_or(true, |time| time.elapsed() >= snapshot_interval) {
+            shared.update(&evolver);
+
+            match snapshots.try_send(Snapshot::new(
+                &evolver,
+                &shared,
+                &tracker,
+                &statistics_export,
+            )) {
+                Ok(_) => {
+                    changed = false;
+                    last_published = Some(Instant::now());
+                }
+                Err(TrySendError::Full(_)) => {}
+                Err(TrySendError::Disconnected(_)) => break 'run,
+            }
+        }
+
+        if !running_flat_out && time_owed < STEPS_FREQUENCY {
+            thread::sleep(IDLE_SLEEP);
+        }
+    }
+
+    evolver.save_hall_of_fame();
+
+    // There's no snapshot left to show this in
+    if let Some(error) = evolver.hall_of_fame_error() {
+        eprintln!("Failed to save the hall of fame: {}", error);
+    }
+}
+
+/// The parts of a [Snapshot] that only change between generations, which are shared rather than copied
+struct SharedState {
+    on_generation: usize,
+    creatures: Arc<Vec<SnapshotCreature>>,
+    offspring: Arc<Vec<Creature>>,
+    best_replay: Option<Arc<Replay>>,
+}
+
+impl SharedState {
+    fn new(evolver: &Evolver) -> SharedState {
+        let creatures = evolver
+            .current_generation()
+            .iter()
+            .map(|simulation| SnapshotCreature::new(simulation.creature()))
+            .collect();
+
+        let offspring = evolver
+            .next_generation()
+            .iter()
+            .map(|simulation| simulation.creature().clone())
+            .collect();
+
+        SharedState {
+            on_generation: evolver.on_generation(),
+            creatures: Arc::new(creatures),
+            offspring: Arc::new(offspring),
+            best_replay: evolver.best_replay().cloned().map(Arc::new),
+        }
+    }
+
+    /// Catches up with the evolver if a new generation has started, or the next one has been made
+    fn update(&mut self, evolver: &Evolver) {
+        if evolver.on_generation() != self.on_generation
+            || evolver.next_generation().len() != self.offspring.len()
+        {
+            *self = SharedState::new(evolver);
+        }
+    }
+}
+
+/// Records the steps of the creature the UI is following, see [EvolverWorker::track]
+#[derive(Default)]
+struct Tracker {
+    creature: Option<Uuid>,
+    /// Shared with the snapshots, and only copied when one still holds it
+    track: Option<Arc<Track>>,
+}
+
+impl Tracker {
+    /// Tracks another creature, or nothing, from scratch
+    fn follow(&mut self, creature: Option<Uuid>) {
+        if creature != self.creature {
+            self.creature = creature;
+            self.track = None;
+        }
+    }
+
+    /// Records the tracked creature's body after a step, starting over when a new generation starts
+    fn record(&mut self, evolver: &Evolver) {
+        if self
+            .track
+            .as_ref()
+            .map_or(false, |track| track.generation != evolver.on_generation())
+        {
+            self.track = None;
+        }
+
+        let steps_left = match evolver.state() {
+            EvolverState::SimulatingGeneration { steps_left } => steps_left,
+            EvolverState::Evolving { .. } => return,
+        };
+        let simulation = match evolver
+            .current_generation()
+            .iter()
+            .find(|simulation| Some(*simulation.creature().id()) == self.creature)
+        {
+            Some(simulation) => simulation,
+            None => return,
+        };
+        let track = self.track.get_or_insert_with(|| {
+            Arc::new(Track::new(simulation.creature(), evolver.on_generation()))
+        });
+
+        Arc::make_mut(track).push(
+            evolver.settings().steps_per_generation() - steps_left,
+            simulation,
+        );
+    }
+}
+
+/// The last [TRACKED_STEPS] of a creature's body, see [EvolverWorker::track]
+#[derive(Clone)]
+pub struct Track {
+    creature: Uuid,
+    generation: usize,
+    node_ids: Vec<Uuid>,
+    muscle_ids: Vec<Uuid>,
+    /// Oldest first
+    steps: VecDeque<TrackedStep>,
+}
+
+impl Track {
+    fn new(creature: &Creature, generation: usize) -> Track {
+        let mut node_ids: Vec<Uuid> = creature.nodes().keys().copied().collect();
+        let mut muscle_ids: Vec<Uuid> = creature.muscles().keys().copied().collect();
+
+        node_ids.sort();
+        muscle_ids.sort();
+
+        Track {
+            creature: *creature.id(),
+            generation,
+            node_ids,
+            muscle_ids,
+            steps: VecDeque::new(),
+        }
+    }
+
+    fn push(&mut self, step: i32, simulation: &Simulation) {
+        // The same step is recorded again when tracking starts while paused
+        if self
+            .steps
+            .back()
+            .map_or(false, |tracked| tracked.step >= step)
+        {
+            return;
+        }
+
+        self.steps.push_back(TrackedStep {
+            step,
+            positions: self
+                .node_ids
+                .iter()
+                .map(|id| simulation.get_position_of_node(*id))
+                .collect(),
+            touching: self
+                .node_ids
+                .iter()
+                .map(|id| simulation.is_node_touching_ground(*id))
+                .collect(),
+            extending: self
+                .muscle_ids
+                .iter()
+                .map(|id| simulation.is_muscle_extending(*id))
+                .collect(),
+        });
+
+        while self
+            .steps
+            .front()
+            .map_or(false, |tracked| tracked.step <= step - TRACKED_STEPS)
+        {
+            self.steps.pop_front();
+        }
+    }
+
+    /// Gets the id of the tracked creature
+    pub fn creature(&self) -> Uuid {
+        self.creature
+    }
+
+    /// Gets the ids of the creature's nodes, sorted
+    pub fn node_ids(&self) -> &[Uuid] {
+        &self.node_ids
+    }
+
+    /// Gets the ids of the creature's muscles, sorted
+    pub fn muscle_ids(&self) -> &[Uuid] {
+        &self.muscle_ids
+    }
+
+    /// Gets the recorded steps, oldest first
+    pub fn steps(&self) -> &VecDeque<TrackedStep> {
+        &self.steps
+    }
+}
+
+/// A tracked creature's body after one step, in the order of its [Track]'s ids
+#[derive(Clone)]
+pub struct TrackedStep {
+    step: i32,
+    positions: Vec<Vector<f32>>,
+    touching: Vec<bool>,
+    extending: Vec<bool>,
+}
+
+impl TrackedStep {
+    /// Gets how many steps into the generation this was
+    pub fn step(&self) -> i32 {
+        self.step
+    }
+
+    /// Gets where each node was
+    pub fn positions(&self) -> &[Vector<f32>] {
+        &self.positions
+    }
+
+    /// Gets whether each node was touching the ground
+    pub fn touching(&self) -> &[bool] {
+        &self.touching
+    }
+
+    /// Gets whether each muscle was extending
+    pub fn extending(&self) -> &[bool] {
+        &self.extending
+    }
+}
+
+/// A creature of a [Snapshot], with its node and muscle ids sorted so their states can be looked up
+struct SnapshotCreature {
+    creature: Creature,
+    node_ids: Vec<Uuid>,
+    muscle_ids: Vec<Uuid>,
+}
+
+impl SnapshotCreature {
+    fn new(creature: &Creature) -> SnapshotCreature {
+        let mut node_ids: Vec<Uuid> = creature.nodes().keys().copied().collect();
+        let mut muscle_ids: Vec<Uuid> = creature.muscles().keys().copied().collect();
+
+        node_ids.sort();
+        muscle_ids.sort();
+
+        SnapshotCreature {
+            creature: creature.clone(),
+            node_ids,
+            muscle_ids,
+        }
+    }
+}
+
+/// The state of a creature's body when a [Snapshot] was taken, in the order of its [SnapshotCreature]'s ids
+struct BodyState {
+    positions: Vec<Vector<f32>>,
+    touching: Vec<bool>,
+    extending: Vec<bool>,
+    score: f32,
+    fitness: f32,
+}
+
+/// What an [Evolver] looked like at one moment, with everything needed to draw and inspect it
+#[derive(Clone)]
+pub struct Snapshot {
+    state: EvolverState,
+    on_generation: usize,
+    settings: EvolverSettings,
+    creatures: Arc<Vec<SnapshotCreature>>,
+    offspring: Arc<Vec<Creature>>,
+    bodies: Arc<Vec<BodyState>>,
+    /// Shared with the evolver, which only ever adds to it
+    generation_statistics: Arc<RwLock<Vec<GenerationStatistics>>>,
+    /// How many generations had statistics when the snapshot was taken
+    statistics_count: usize,
+    best_replay: Option<Arc<Replay>>,
+    /// Shared with the evolver and live, unlike everything else here
+    ancestry: Arc<RwLock<Ancestry>>,
+    track: Option<Arc<Track>>,
+    statistics_export: Option<Result<PathBuf, String>>,
+    hall_of_fame_error: Option<String>,
+}
+
+impl Snapshot {
+    fn new(
+        evolver: &Evolver,
+        shared: &SharedState,
+        tracker: &Tracker,
+        statistics_export: &Option<Result<PathBuf, String>>,
+    ) -> Snapshot {
+        let fitness = evolver.fitness();
+        let bodies = evolver
+            .current_generation()
+            .iter()
+            .zip(shared.creatures.iter())
+            .map(|(simulation, creature)| BodyState {
+                positions: creature
+                    .node_ids
+                    .iter()
+                    .map(|id| simulation.get_position_of_node(*id))
+                    .collect(),
+                touching: creature
+                    .node_ids
+                    .iter()
+                    .map(|id| simulation.is_node_touching_ground(*id))
+                    .collect(),
+                extending: creature
+                    .muscle_ids
+                    .iter()
+                    .map(|id| simulation.is_muscle_extending(*id))
+                    .collect(),
+                score: simulation.get_score(),
+                fitness: simulation.get_fitness(fitness),
+            })
+            .collect();
+
+        Snapshot {
+            state: evolver.state(),
+            on_generation: evolver.on_generation(),
+            settings: evolver.settings().clone(),
+            creatures: shared.creatures.clone(),
+            offspring: shared.offspring.clone(),
+            bodies: Arc::new(bodies),
+            generation_statistics: evolver.generation_statistics.clone(),
+            statistics_count: evolver.generation_statistics().len(),
+            best_replay: shared.best_replay.clone(),
+            ancestry: evolver.ancestry.clone(),
+            track: tracker.track.clone(),
+            // A file that stopped being written to matters more than where it was
+            statistics_export: match evolver.statistics_error() {
+                Some(error) => Some(Err(error.to_string())),
+                None => statistics_export.clone(),
+            },
+            hall_of_fame_error: evolver.hall_of_fame_error().map(|error| error.to_string()),
+        }
+    }
+
+    /// Gets the state the evolver was in
+    pub fn state(&self) -> EvolverState {
+        self.state
+    }
+
+    /// Returns the number of the current generation
+    pub fn on_generation(&self) -> usize {
+        self.on_generation
+    }
+
+    /// Gets the settings the evolver runs with
+    pub fn settings(&self) -> &EvolverSettings {
+        &self.settings
+    }
+
+    /// Gets every creature of the current generation, in the order the evolver keeps them
+    pub fn current_generation(&self) -> Vec<CreatureSnapshot<'_>> {
+        self.creatures
+            .iter()
+            .zip(self.bodies.iter())
+            .map(|(creature, body)| CreatureSnapshot {
+                creature,
+                body,
+                terrain: self.settings.environment.terrain,
+            })
+            .collect()
+    }
+
+    /// Gets the offspring of the current generation while it's evolved, see [Evolver::next_generation]
+    pub fn offspring(&self) -> &[Creature] {
+        &self.offspring
+    }
+
+    /// Gets the statistics of past generations. The evolver can't record the next generation until this is
+    /// dropped
+    pub fn generation_statistics(&self) -> GenerationStatisticsGuard<'_> {
+        GenerationStatisticsGuard {
+            statistics: util::read(&self.generation_statistics),
+            count: self.statistics_count,
+        }
+    }
+
+    /// Gets a [Replay] of the best creature of the last generation, if there has been one
+    pub fn best_replay(&self) -> Option<&Replay> {
+        self.best_replay.as_deref()
+    }
+
+    /// Gets every creature of the run so far and who they came from. The evolver can't record the next
+    /// generation until this is dropped.
+    ///
+    /// Unlike the rest of the snapshot, this is the evolver's live ancestry rather than how it was when the
+    /// snapshot was taken. It can include generations and scores recorded since, so it shouldn't be compared
+    /// with the snapshot's other fields
+    pub fn ancestry(&self) -> RwLockReadGuard<'_, Ancestry> {
+        util::read(&self.ancestry)
+    }
+
+    /// Gets the recent steps of the creature the worker was asked to track, once it has taken one
+    pub fn track(&self) -> Option<&Track> {
+        self.track.as_deref()
+    }
+
+    /// Gets where statistics were last asked to be written to, or what went wrong starting to write them
+    pub fn statistics_export(&self) -> Option<&Result<PathBuf, String>> {
+        self.statistics_export.as_ref()
+    }
+
+    /// Gets why the hall of fame failed to save, if it hasn't saved since
+    pub fn hall_of_fame_error(&self) -> Option<&str> {
+        self.hall_of_fame_error.as_deref()
+    }
+}
+
+/// The statistics of the generations before a [Snapshot] was taken, see [Snapshot::generation_statistics]
+pub struct GenerationStatisticsGuard<'a> {
+    statistics: RwLockReadGuard<'a, Vec<GenerationStatistics>>,
+    count: usize,
+}
+
+impl Deref for GenerationStatisticsGuard<'_> {
+    type Target = [GenerationStatistics];
+
+    fn deref(&self) -> &Self::Target {
+        &self.statistics[..self.count]
+    }
+}
+
+/// A single creature of a [Snapshot], which can be painted like a live simulation
+#[derive(Clone, Copy)]
+pub struct CreatureSnapshot<'a> {
+    creature: &'a SnapshotCreature,
+    body: &'a BodyState,
+    terrain: Terrain,
+}
+
+impl CreatureSnapshot<'_> {
+    /// Gets the creature's fitness, as measured by the [Fitness](crate::simulation::Fitness) the run selects by
+    pub fn get_fitness(&self) -> f32 {
+        self.body.fitness
+    }
+
+    /// Returns true if a node was touching the ground when the snapshot was taken
+    pub fn is_node_touching_ground(&self, id: Uuid) -> bool {
+        let index = self.creature.node_ids.binary_search(&id).unwrap();
+
+        self.body.touching[index]
+    }
+}
+
+impl SimulationView for CreatureSnapshot<'_> {
+    fn creature(&self) -> &Creature {
+        &self.creature.creature
+    }
+
+    fn get_position_of_node(&self, id: Uuid) -> Vector<f32> {
+        let index = self.creature.node_ids.binary_search(&id).unwrap();
+
+        self.body.positions[index]
+    }
+
+    fn is_muscle_extending(&self, id: Uuid) -> bool {
+        let index = self.creature.muscle_ids.binary_search(&id).unwrap();
+
+        self.body.extending[index]
+    }
+
+    fn get_score(&self) -> f32 {
+        self.body.score
+    }
+
+    fn terrain(&self) -> Terrain {
+        self.terrain
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Waits for a snapshot of at least a certain generation, failing if it takes too long
+    fn wait_for_generation(worker: &EvolverWorker, generation: usize) -> Snapshot {
+        let deadline = Instant::now() + Duration::from_secs(30);
+
+        loop {
+            assert!(
+                Instant::now() < deadline,
+                "Generation {} was never published",
+                generation
+            );
+
+            match worker.wait_for_snapshot(Duration::from_secs(1)) {
+                Some(snapshot) if snapshot.on_generation() >= generation => return snapshot,
+                _ => {}
+            }
+        }
+    }
+
+    fn small_settings() -> EvolverSettings {
+        EvolverSettings {
+            population: 4,
+            evaluation_seconds: 1.0,
+            seed: Some(7),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    pub fn worker() {
+        let worker = EvolverWorker::spawn(|| Evolver::with_settings(small_settings(), Vec::new()));
+
+        worker.set_paused(true);
+        worker.finish_generation();
+
+        let snapshot = wait_for_generation(&worker, 2);
+
+        assert_eq!(snapshot.on_generation(), 2);
+
+        let generation = snapshot.current_generation();
+
+        assert_eq!(generation.len(), 4);
+        assert_eq!(snapshot.generation_statistics().len(), 1);
+        assert!(snapshot.best_replay().is_some());
+
+        for creature in &generation {
+            for id in creature.creature().nodes().keys() {
+                assert!(creature.get_position_of_node(*id).x.is_finite());
+            }
+        }
+
+        // The statistics are shared rather than copied, but still only go up to when the snapshot was taken
+        worker.finish_generation();
+
+        let next_snapshot = wait_for_generation(&worker, 3);
+
+        assert_eq!(next_snapshot.generation_statistics().len(), 2);
+        assert_eq!(snapshot.generation_statistics().len(), 1);
+
+        // While the ancestry is live, so an old snapshot sees everything recorded since
+        let recorded = next_snapshot.ancestry().len();
+
+        assert_eq!(snapshot.ancestry().len(), recorded);
+    }
+
+    #[test]
+    pub fn unchanged_settings() {
+        let worker = EvolverWorker::spawn(|| Evolver::with_settings(small_settings(), Vec::new()));
+
+        worker.set_paused(true);
+
+        while worker
+            .wait_for_snapshot(Duration::from_millis(100))
+            .is_some()
+        {}
+
+        // Like the UI does every frame
+        for _ in 0..10 {
+            worker.set_speed(1.0);
+            worker.set_paused(true);
+            worker.set_turbo(false);
+        }
+
+        assert!(worker
+            .wait_for_snapshot(Duration::from_millis(100))
+            .is_none());
+    }
+
+    #[test]
+    pub fn track() {
+        let worker = EvolverWorker::spawn(|| Evolver::with_settings(small_settings(), Vec::new()));
+
+        worker.set_paused(true);
+
+        let snapshot = wait_for_generation(&worker, 1);
+        let id = *snapshot.current_generation()[0].creature().id();
+
+        worker.track(Some(id));
+
+        for _ in 0..3 {
+            worker.step();
+        }
+
+        // Every step is recorded, however few snapshots are published
+        let deadline = Instant::now() + Duration::from_secs(30);
+
+        loop {
+            assert!(Instant::now() < deadline, "The steps were never tracked");
+
+            let snapshot = match worker.wait_for_snapshot(Duration::from_secs(1)) {
+                Some(snapshot) => snapshot,
+                None => continue,
+            };
+            let track = match snapshot.track() {
+                Some(track) if track.steps().len() == 4 => track,
+                _ => continue,
+            };
+            let steps: Vec<i32> = track.steps().iter().map(TrackedStep::step).collect();
+
+            assert_eq!(track.creature(), id);
+            assert_eq!(steps, vec![0, 1, 2, 3]);
+            assert_eq!(track.steps()[0].positions().len(), track.node_ids().len());
+            break;
+        }
+    }
+
+    #[test]
+    pub fn turbo() {
+        let worker = EvolverWorker::spawn(|| Evolver::with_settings(small_settings(), Vec::new()));
+
+        // Far quicker than the 6 seconds each generation takes in real time
+        worker.set_turbo(true);
+
+        let snapshot = wait_for_generation(&worker, 4);
+
+        assert!(snapshot.generation_statistics().len() >= 3);
+    }
+}
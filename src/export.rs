@@ -0,0 +1,182 @@
+//! Exports [Simulation](crate::simulation::Simulation)s and [Replay]s to files, without needing a window
+
+mod raster;
+mod svg;
+
+pub use raster::Rasterizer;
+pub use svg::creature_to_svg;
+
+use std::{
+    fs::{self, File},
+    io::{self, BufWriter},
+    path::Path,
+};
+
+use image::{
+    codecs::gif::{GifEncoder, Repeat},
+    Delay, Frame, ImageError,
+};
+
+use crate::{
+    creature::Creature,
+    evolver::STEPS_PER_GENERATION,
+    replay::{Replay, REPLAY_FILE_EXTENSION, REPLAY_STEPS_PER_FRAME},
+    simulation::{Environment, Simulation},
+};
+
+pub const DEFAULT_IMAGE_WIDTH: u32 = 960;
+pub const DEFAULT_IMAGE_HEIGHT: u32 = 540;
+
+/// Exports a replay as an animated GIF that loops forever
+pub fn export_gif(replay: &Replay, rasterizer: &Rasterizer, path: &Path) -> io::Result<()> {
+    let mut encoder = GifEncoder::new(BufWriter::new(File::create(path)?));
+    let delay = Delay::from_saturating_duration(replay.frame_duration());
+
+    encoder.set_repeat(Repeat::Infinite).map_err(to_io_error)?;
+
+    let frames = (0..replay.frame_count())
+        .map(|index| Frame::from_parts(rasterizer.render(&replay.frame(index)), 0, 0, delay));
+
+    encoder.encode_frames(frames).map_err(to_io_error)
+}
+
+/// Exports a replay as a numbered sequence of PNGs (`frame_00000.png`, `frame_00001.png`, ...) in a directory,
+/// creating the directory if it's missing
+pub fn export_png_sequence(
+    replay: &Replay,
+    rasterizer: &Rasterizer,
+    directory: &Path,
+) -> io::Result<()> {
+    fs::create_dir_all(directory)?;
+
+    for index in 0..replay.frame_count() {
+        rasterizer
+            .render(&replay.frame(index))
+            .save(directory.join(format!("frame_{:05}.png", index)))
+            .map_err(to_io_error)?;
+    }
+
+    Ok(())
+}
+
+/// Exports a [Creature]'s body plan as an SVG, see [creature_to_svg]
+pub fn export_svg(creature: &Creature, path: &Path) -> io::Result<()> {
+    fs::write(path, creature_to_svg(creature))
+}
+
+/// Records a [Creature] in an [Environment] for one generation's worth of steps
+pub fn record_creature(creature: Creature, environment: Environment) -> Replay {
+    let mut simulation = Simulation::with_environment(creature, environment);
+
+    simulation.start_recording(REPLAY_STEPS_PER_FRAME);
+
+    for _ in 0..STEPS_PER_GENERATION {
+        simulation.step();
+    }
+
+    simulation.replay().unwrap()
+}
+
+/// Exports a saved replay or creature file, simulating the creature if needed. Outputs ending in `.gif` are
+/// exported as GIFs, outputs ending in `.svg` as the creature's body plan, and anything else as a directory of PNGs
+pub fn export_file(input: &Path, output: &Path) -> io::Result<()> {
+    let is_replay = input
+        .to_string_lossy()
+        .ends_with(&format!(".{}", REPLAY_FILE_EXTENSION));
+    let has_extension = |extension: &str| {
+        output.extension().map_or(false, |output_extension| {
+            output_extension.eq_ignore_ascii_case(extension)
+        })
+    };
+
+    if has_extension("svg") {
+        let creature = if is_replay {
+            Replay::load(input)?.creature().clone()
+        } else {
+            Creature::load(input)?
+        };
+
+        return export_svg(&creature, output);
+    }
+
+    let replay = if is_replay {
+        Replay::load(input)?
+    } else {
+        record_creature(Creature::load(input)?, Environment::default())
+    };
+
+    let rasterizer = Rasterizer::new(DEFAULT_IMAGE_WIDTH, DEFAULT_IMAGE_HEIGHT);
+
+    if has_extension("gif") {
+        export_gif(&replay, &rasterizer, output)
+    } else {
+        export_png_sequence(&replay, &rasterizer, output)
+    }
+}
+
+/// Converts an [ImageError] into an [io::Error]
+fn to_io_error(error: ImageError) -> io::Error {
+    match error {
+        ImageError::IoError(error) => error,
+        error => io::Error::new(io::ErrorKind::Other, error),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        creature::{CreatureBuilder, Position},
+        simulation::{FLOOR_TOP_Y, WORLD_X_SIZE},
+        ui::{GROUND_COLOR, SKY_COLOR},
+        util::TestDirectory,
+    };
+
+    #[test]
+    pub fn render() {
+        let creature = CreatureBuilder::random()
+            .translate_bottom_center_to(&Position::new(WORLD_X_SIZE / 2.0, FLOOR_TOP_Y))
+            .build();
+        let image = Rasterizer::new(200, 100).render(&Simulation::new(creature));
+
+        assert_eq!(image.dimensions(), (200, 100));
+        assert_eq!(image.get_pixel(0, 0).0[..3], SKY_COLOR.to_array()[..3]);
+        assert!((0..200).any(|x| image.get_pixel(x, 99).0[..3] == GROUND_COLOR.to_array()[..3]));
+    }
+
+    #[test]
+    pub fn svg() {
+        let creature = CreatureBuilder::random().build();
+        let svg = creature_to_svg(&creature);
+
+        assert!(svg.starts_with("<svg"));
+        assert_eq!(svg.matches("<circle").count(), creature.nodes().len());
+        assert_eq!(svg.matches("<line").count(), creature.muscles().len());
+        assert_eq!(svg.matches(" / C ").count(), creature.muscles().len());
+    }
+
+    #[test]
+    pub fn export() {
+        let creature = CreatureBuilder::random()
+            .translate_bottom_center_to(&Position::new(WORLD_X_SIZE / 2.0, FLOOR_TOP_Y))
+            .build();
+        let mut simulation = Simulation::new(creature);
+
+        simulation.start_recording(10);
+
+        for _ in 0..30 {
+            simulation.step();
+        }
+
+        let replay = simulation.replay().unwrap();
+        let rasterizer = Rasterizer::new(64, 36);
+        let test_directory = TestDirectory::new("export");
+        let directory = test_directory.path().join("frames");
+
+        export_png_sequence(&replay, &rasterizer, &directory).unwrap();
+        export_gif(&replay, &rasterizer, &directory.join("replay.gif")).unwrap();
+
+        assert!(directory.join("frame_00003.png").exists());
+        assert!(directory.join("replay.gif").exists());
+    }
+}
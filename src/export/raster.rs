@@ -0,0 +1,312 @@
+use egui::Color32;
+use image::{Rgba, RgbaImage};
+
+use crate::{
+    simulation::{SimulationView, Terrain, FLOOR_HEIGHT, FLOOR_TOP_Y},
+    ui::{
+        self, Camera, ScoreLine, CREATURE_SCORE_TEXT_SIZE, DISTANCE_LINE_THICKNESS, GROUND_COLOR,
+        SCORE_LINE_TEXT_SIZE, SKY_COLOR,
+    },
+    util,
+};
+
+const REFERENCE_SCREEN_HEIGHT: f32 = 1080.0; // The screen height text sizes are designed for
+const GLYPH_WIDTH: u32 = 3;
+const GLYPH_HEIGHT: u32 = 5;
+
+/// Draws [Simulation]s into images without a window or GPU, matching how the UI paints them
+pub struct Rasterizer {
+    width: u32,
+    height: u32,
+}
+
+impl Rasterizer {
+    /// Creates a rasterizer that draws images of a certain size
+    pub fn new(width: u32, height: u32) -> Rasterizer {
+        Rasterizer { width, height }
+    }
+
+    /// Draws a [Simulation], or anything else that can be viewed like one, along with the scenery
+    /// around it, following it like the UI follows the leader
+    pub fn render(&self, simulation: &impl SimulationView) -> RgbaImage {
+        let mut image = RgbaImage::new(self.width, self.height);
+        let mut camera = Camera::new(egui::Vec2::new(self.width as f32, self.height as f32));
+
+        camera.follow(std::slice::from_ref(simulation), None);
+
+        self.draw_scenery(&mut image, simulation.terrain(), &camera);
+        self.draw_simulation(&mut image, simulation, &camera);
+
+        image
+    }
+
+    /// Draws the sky, ground, terrain and score lines, like [App::paint_scenery](crate::ui)
+    fn draw_scenery(&self, image: &mut RgbaImage, terrain: Terrain, camera: &Camera) {
+        let width = self.width as f32;
+        let height = self.height as f32;
+        let floor_top = camera.y_to_screen(FLOOR_TOP_Y);
+
+        fill_rect(image, 0.0, 0.0, width, height, SKY_COLOR);
+        fill_rect(image, 0.0, floor_top, width, height, GROUND_COLOR);
+
+        let (visible_top_left, visible_bottom_right) = camera.visible_bounds();
+
+        for (top_left, bottom_right) in
+            terrain.obstacles_between(visible_top_left.x, visible_bottom_right.x)
+        {
+            let min = camera.to_screen(&top_left);
+            let max = camera.to_screen(&bottom_right);
+
+            fill_rect(image, min.x, min.y, max.x, max.y, GROUND_COLOR);
+        }
+
+        for ScoreLine {
+            score,
+            x,
+            height_scale,
+            labeled,
+        } in camera.score_lines()
+        {
+            let y = camera.y_to_screen(FLOOR_TOP_Y + (FLOOR_HEIGHT * height_scale));
+
+            fill_rect(
+                image,
+                camera.x_to_screen(x - (DISTANCE_LINE_THICKNESS / 2.0)),
+                floor_top,
+                camera.x_to_screen(x + (DISTANCE_LINE_THICKNESS / 2.0)),
+                y,
+                Color32::WHITE,
+            );
+
+            if labeled {
+                self.draw_text(
+                    image,
+                    &format!("{:.2}m", score),
+                    camera.x_to_screen(x),
+                    y,
+                    SCORE_LINE_TEXT_SIZE,
+                    Color32::WHITE,
+                );
+            }
+        }
+    }
+
+    /// Draws the muscles, nodes and score of a creature, like [App::paint_simulation](crate::ui)
+    fn draw_simulation(
+        &self,
+        image: &mut RgbaImage,
+        simulation: &impl SimulationView,
+        camera: &Camera,
+    ) {
+        let creature = simulation.creature();
+        let colors = creature.colors();
+        let movement_parameters = creature.movement_parameters();
+
+        for (id, muscle) in creature.muscles() {
+            let from = simulation.get_position_of_node(muscle.from_id);
+            let to = simulation.get_position_of_node(muscle.to_id);
+            let normal_length = movement_parameters[id].muscle_length();
+            let thickness = ui::muscle_thickness(util::distance(&from, &to), normal_length);
+
+            let color = if simulation.is_muscle_extending(*id) {
+                colors.muscle_extended()
+            } else {
+                colors.muscle_contracted()
+            };
+
+            let from_pixel = camera.to_screen(&from);
+            let to_pixel = camera.to_screen(&to);
+
+            draw_line(
+                image,
+                (from_pixel.x, from_pixel.y),
+                (to_pixel.x, to_pixel.y),
+                camera.length_to_screen(thickness),
+                color,
+            );
+        }
+
+        for (id, node) in creature.nodes() {
+            let position = camera.to_screen(&simulation.get_position_of_node(*id));
+
+            fill_circle(
+                image,
+                position.x,
+                position.y,
+                camera.length_to_screen(node.size / 2.0),
+                colors.node(),
+            );
+        }
+
+        let text_position = camera.to_screen(&simulation.get_text_position());
+        let text_height = self.text_scale(CREATURE_SCORE_TEXT_SIZE) * GLYPH_HEIGHT as f32;
+
+        self.draw_text(
+            image,
+            &format!("{:.2}m", simulation.get_score()),
+            text_position.x,
+            text_position.y - text_height * 2.0,
+            CREATURE_SCORE_TEXT_SIZE,
+            colors.score_text(),
+        );
+    }
+
+    /// Gets how many pixels wide each pixel of a glyph is for a text size
+    fn text_scale(&self, size: f32) -> f32 {
+        (size * self.height as f32 / REFERENCE_SCREEN_HEIGHT / GLYPH_HEIGHT as f32)
+            .round()
+            .max(1.0)
+    }
+
+    /// Draws text centered on x with its top at y, using a small built in pixel font
+    fn draw_text(
+        &self,
+        image: &mut RgbaImage,
+        text: &str,
+        center_x: f32,
+        top_y: f32,
+        size: f32,
+        color: Color32,
+    ) {
+        let scale = self.text_scale(size);
+        let advance = (GLYPH_WIDTH + 1) as f32 * scale;
+        let mut x = center_x - (advance * text.chars().count() as f32 - scale) / 2.0;
+
+        for character in text.chars() {
+            let rows = glyph(character);
+
+            for (row_index, row) in rows.iter().enumerate() {
+                for column in 0..GLYPH_WIDTH {
+                    if row & (1 << (GLYPH_WIDTH - 1 - column)) == 0 {
+                        continue;
+                    }
+
+                    let pixel_x = x + column as f32 * scale;
+                    let pixel_y = top_y + row_index as f32 * scale;
+
+                    fill_rect(
+                        image,
+                        pixel_x,
+                        pixel_y,
+                        pixel_x + scale,
+                        pixel_y + scale,
+                        color,
+                    );
+                }
+            }
+
+            x += advance;
+        }
+    }
+}
+
+/// Gets the rows of a glyph in the built in pixel font, where each bit is a pixel
+fn glyph(character: char) -> [u8; GLYPH_HEIGHT as usize] {
+    match character {
+        '0' => [0b111, 0b101, 0b101, 0b101, 0b111],
+        '1' => [0b010, 0b110, 0b010, 0b010, 0b111],
+        '2' => [0b111, 0b001, 0b111, 0b100, 0b111],
+        '3' => [0b111, 0b001, 0b111, 0b001, 0b111],
+        '4' => [0b101, 0b101, 0b111, 0b001, 0b001],
+        '5' => [0b111, 0b100, 0b111, 0b001, 0b111],
+        '6' => [0b111, 0b100, 0b111, 0b101, 0b111],
+        '7' => [0b111, 0b001, 0b001, 0b001, 0b001],
+        '8' => [0b111, 0b101, 0b111, 0b101, 0b111],
+        '9' => [0b111, 0b101, 0b111, 0b001, 0b111],
+        '.' => [0b000, 0b000, 0b000, 0b000, 0b010],
+        '-' => [0b000, 0b000, 0b111, 0b000, 0b000],
+        'm' => [0b000, 0b110, 0b111, 0b101, 0b101],
+        _ => [0b000; GLYPH_HEIGHT as usize],
+    }
+}
+
+/// Converts a color into a pixel
+fn to_pixel(color: Color32) -> Rgba<u8> {
+    Rgba([color.r(), color.g(), color.b(), 255])
+}
+
+/// Fills the pixels whose centers are inside a rectangle, clipped to the image
+fn fill_rect(
+    image: &mut RgbaImage,
+    min_x: f32,
+    min_y: f32,
+    max_x: f32,
+    max_y: f32,
+    color: Color32,
+) {
+    let pixel = to_pixel(color);
+    let x_range = pixel_range(min_x, max_x, image.width());
+    let y_range = pixel_range(min_y, max_y, image.height());
+
+    for y in y_range {
+        for x in x_range.clone() {
+            image.put_pixel(x, y, pixel);
+        }
+    }
+}
+
+/// Fills the pixels whose centers are inside a circle, clipped to the image
+fn fill_circle(image: &mut RgbaImage, center_x: f32, center_y: f32, radius: f32, color: Color32) {
+    let pixel = to_pixel(color);
+    let x_range = pixel_range(center_x - radius, center_x + radius, image.width());
+    let y_range = pixel_range(center_y - radius, center_y + radius, image.height());
+
+    for y in y_range {
+        for x in x_range.clone() {
+            let dx = x as f32 + 0.5 - center_x;
+            let dy = y as f32 + 0.5 - center_y;
+
+            if dx * dx + dy * dy <= radius * radius {
+                image.put_pixel(x, y, pixel);
+            }
+        }
+    }
+}
+
+/// Draws a line with a thickness, filling the pixels whose centers are close enough to it
+fn draw_line(
+    image: &mut RgbaImage,
+    from: (f32, f32),
+    to: (f32, f32),
+    thickness: f32,
+    color: Color32,
+) {
+    let pixel = to_pixel(color);
+    let half_thickness = (thickness / 2.0).max(0.5);
+    let x_range = pixel_range(
+        from.0.min(to.0) - half_thickness,
+        from.0.max(to.0) + half_thickness,
+        image.width(),
+    );
+    let y_range = pixel_range(
+        from.1.min(to.1) - half_thickness,
+        from.1.max(to.1) + half_thickness,
+        image.height(),
+    );
+
+    let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+    let length_squared = (dx * dx + dy * dy).max(f32::EPSILON);
+
+    for y in y_range {
+        for x in x_range.clone() {
+            let (px, py) = (x as f32 + 0.5, y as f32 + 0.5);
+
+            // Distance from the pixel to the closest point on the line
+            let t = (((px - from.0) * dx + (py - from.1) * dy) / length_squared).clamp(0.0, 1.0);
+            let (closest_x, closest_y) = (from.0 + t * dx, from.1 + t * dy);
+            let distance_squared = (px - closest_x).powi(2) + (py - closest_y).powi(2);
+
+            if distance_squared <= half_thickness * half_thickness {
+                image.put_pixel(x, y, pixel);
+            }
+        }
+    }
+}
+
+/// Gets the range of pixels whose centers are between min and max, clipped to [0, size)
+fn pixel_range(min: f32, max: f32, size: u32) -> std::ops::Range<u32> {
+    let start = (min - 0.5).ceil().clamp(0.0, size as f32) as u32;
+    let end = ((max - 0.5).floor() + 1.0).clamp(0.0, size as f32) as u32;
+
+    start..end.max(start)
+}
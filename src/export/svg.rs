@@ -0,0 +1,133 @@
+use std::fmt::Write;
+
+use egui::Color32;
+
+use crate::{
+    creature::{Controller, Creature},
+    simulation::STEPS_PER_SECOND,
+    ui, util,
+};
+
+const MARGIN: f32 = 40.0;
+const LABEL_FONT_SIZE: f32 = 8.0;
+const TITLE_FONT_SIZE: f32 = 10.0;
+const LABEL_COLOR: Color32 = Color32::from_rgb(40, 40, 40);
+
+/// Draws a [Creature]'s body plan as an SVG, with each muscle labeled with its movement periods
+pub fn creature_to_svg(creature: &Creature) -> String {
+    let nodes = creature.nodes();
+    let colors = creature.colors();
+
+    // Bounds, including the size of each node
+    let min_x = nodes
+        .values()
+        .map(|node| node.position.x - node.size / 2.0)
+        .min_by(util::cmp_f32)
+        .unwrap_or(0.0)
+        - MARGIN;
+    let min_y = nodes
+        .values()
+        .map(|node| node.position.y - node.size / 2.0)
+        .min_by(util::cmp_f32)
+        .unwrap_or(0.0)
+        - MARGIN;
+    let max_x = nodes
+        .values()
+        .map(|node| node.position.x + node.size / 2.0)
+        .max_by(util::cmp_f32)
+        .unwrap_or(0.0)
+        + MARGIN;
+    let max_y = nodes
+        .values()
+        .map(|node| node.position.y + node.size / 2.0)
+        .max_by(util::cmp_f32)
+        .unwrap_or(0.0)
+        + MARGIN;
+
+    let mut svg = String::new();
+
+    // Writing to a String can't fail, so the results are ignored
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="{} {} {} {}" width="{}" height="{}">"#,
+        min_x,
+        min_y,
+        max_x - min_x,
+        max_y - min_y,
+        (max_x - min_x) * 4.0,
+        (max_y - min_y) * 4.0
+    );
+
+    let title = match creature.controller() {
+        Controller::Timed => format!("Creature {}", creature.id()),
+        Controller::Neural(_) => format!("Creature {} (neural controller)", creature.id()),
+    };
+
+    let _ = writeln!(
+        svg,
+        r#"  <text x="{}" y="{}" font-family="sans-serif" font-size="{}" fill="{}">{}</text>"#,
+        min_x + LABEL_FONT_SIZE,
+        min_y + TITLE_FONT_SIZE * 1.5,
+        TITLE_FONT_SIZE,
+        to_hex(LABEL_COLOR),
+        title
+    );
+
+    // Muscles, sorted so the output is stable
+    let mut muscles: Vec<_> = creature.muscles().iter().collect();
+    muscles.sort_by_key(|(id, _)| **id);
+
+    for (id, muscle) in &muscles {
+        let from = &nodes[&muscle.from_id].position;
+        let to = &nodes[&muscle.to_id].position;
+        let length = from.distance_to(to);
+
+        let _ = writeln!(
+            svg,
+            r#"  <line x1="{}" y1="{}" x2="{}" y2="{}" stroke="{}" stroke-width="{}" stroke-linecap="round"/>"#,
+            from.x,
+            from.y,
+            to.x,
+            to.y,
+            to_hex(colors.muscle_extended()),
+            ui::muscle_thickness(length, length)
+        );
+
+        if let Some(movement_parameters) = creature.movement_parameters().get(id) {
+            let _ = writeln!(
+                svg,
+                r#"  <text x="{}" y="{}" font-family="sans-serif" font-size="{}" text-anchor="middle" fill="{}">E {:.2}s / C {:.2}s</text>"#,
+                (from.x + to.x) / 2.0,
+                (from.y + to.y) / 2.0 - LABEL_FONT_SIZE / 2.0,
+                LABEL_FONT_SIZE,
+                to_hex(LABEL_COLOR),
+                movement_parameters.extension_period() as f32 / STEPS_PER_SECOND as f32,
+                movement_parameters.contraction_period() as f32 / STEPS_PER_SECOND as f32
+            );
+        }
+    }
+
+    // Nodes, sorted so the output is stable
+    let mut nodes: Vec<_> = nodes.values().collect();
+    nodes.sort_by_key(|node| node.id);
+
+    for node in nodes {
+        let _ = writeln!(
+            svg,
+            r#"  <circle cx="{}" cy="{}" r="{}" fill="{}"/>"#,
+            node.position.x,
+            node.position.y,
+            node.size / 2.0,
+            to_hex(colors.node())
+        );
+    }
+
+    svg.push_str("</svg>\n");
+
+    svg
+}
+
+/// Converts a color to a hex string, like `#ff8800`
+fn to_hex(color: Color32) -> String {
+    format!("#{:02x}{:02x}{:02x}", color.r(), color.g(), color.b())
+}
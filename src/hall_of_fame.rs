@@ -0,0 +1,237 @@
+//! Keeps the best creatures ever evolved, across runs, in a local data directory
+
+use std::{
+    fs::{self, File},
+    io::{self, BufReader, BufWriter},
+    path::{Path, PathBuf},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{creature::Creature, evolver::EvolverSettings, util};
+
+/// The directory the hall of fame is kept in by default, relative to the working directory
+pub const HALL_OF_FAME_DIRECTORY: &str = "hall_of_fame";
+/// How many of the furthest creatures ever are kept
+pub const ALL_TIME_ENTRIES: usize = 20;
+/// How many generation bests are kept, forgetting the oldest first
+pub const GENERATION_ENTRIES: usize = 200;
+const FILE_NAME: &str = "hall_of_fame.json";
+const SAVE_INTERVAL: Duration = Duration::from_secs(5); // The least time between saves while recording
+
+/// A creature that made it into the [HallOfFame], along with the run it came from
+#[derive(Clone, Serialize, Deserialize)]
+pub struct HallOfFameEntry {
+    pub creature: Creature,
+    /// How far the creature got, in meters
+    pub score: f32,
+    /// The creature's fitness, as measured by the [Fitness](crate::simulation::Fitness) in the settings
+    pub fitness: f32,
+    pub generation: usize,
+    /// The id of the run the creature was evolved in
+    pub run_id: Uuid,
+    /// The seed of the run, which repeats it along with the settings
+    pub seed: u64,
+    pub settings: EvolverSettings,
+    /// When the creature was entered, in seconds since the Unix epoch
+    pub recorded_at: u64,
+}
+
+impl HallOfFameEntry {
+    /// Creates an entry for a creature that was just evolved
+    pub fn new(
+        creature: Creature,
+        score: f32,
+        fitness: f32,
+        generation: usize,
+        run_id: Uuid,
+        seed: u64,
+        settings: EvolverSettings,
+    ) -> HallOfFameEntry {
+        let recorded_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+
+        HallOfFameEntry {
+            creature,
+            score,
+            fitness,
+            generation,
+            run_id,
+            seed,
+            settings,
+            recorded_at,
+        }
+    }
+}
+
+/// The best creature of each recent generation and the furthest creatures ever, kept in a file
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HallOfFame {
+    /// Oldest first
+    generation_bests: Vec<HallOfFameEntry>,
+    /// Furthest first
+    all_time: Vec<HallOfFameEntry>,
+    #[serde(skip)]
+    path: PathBuf,
+    #[serde(skip)]
+    last_saved: Option<Instant>,
+    #[serde(skip)]
+    unsaved: bool,
+}
+
+impl HallOfFame {
+    /// Gets the path of the hall of fame inside [HALL_OF_FAME_DIRECTORY]
+    pub fn default_path() -> PathBuf {
+        Path::new(HALL_OF_FAME_DIRECTORY).join(FILE_NAME)
+    }
+
+    /// Loads the hall of fame kept at a path, which starts empty if there's no file yet
+    pub fn load(path: &Path) -> io::Result<HallOfFame> {
+        let mut hall_of_fame = match File::open(path) {
+            Ok(file) => serde_json::from_reader(BufReader::new(file)).map_err(io::Error::from)?,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => HallOfFame::default(),
+            Err(error) => return Err(error),
+        };
+
+        hall_of_fame.path = path.to_path_buf();
+
+        Ok(hall_of_fame)
+    }
+
+    /// Gets where the hall of fame is kept
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Gets the best creature of each recent generation, oldest first
+    pub fn generation_bests(&self) -> &[HallOfFameEntry] {
+        &self.generation_bests
+    }
+
+    /// Gets the furthest creatures ever, furthest first
+    pub fn all_time(&self) -> &[HallOfFameEntry] {
+        &self.all_time
+    }
+
+    /// Enters the best creature of a generation, which also joins the all time entries if it's far enough.
+    /// Saves if it hasn't in a while, so call [HallOfFame::save] once done recording
+    pub fn record(&mut self, entry: HallOfFameEntry) -> io::Result<()> {
+        let id = *entry.creature.id();
+
+        // A creature can be best more than once when copied into a run, but is only listed once of all time
+        match self
+            .all_time
+            .iter()
+            .position(|other| *other.creature.id() == id)
+        {
+            Some(index) if self.all_time[index].score >= entry.score => {}
+            existing => {
+                if let Some(index) = existing {
+                    self.all_time.remove(index);
+                }
+
+                self.all_time.push(entry.clone());
+                self.all_time
+                    .sort_by(|a, b| util::cmp_f32(&b.score, &a.score));
+                self.all_time.truncate(ALL_TIME_ENTRIES);
+            }
+        }
+
+        self.generation_bests.push(entry);
+
+        let excess = self
+            .generation_bests
+            .len()
+            .saturating_sub(GENERATION_ENTRIES);
+        self.generation_bests.drain(..excess);
+
+        self.unsaved = true;
+
+        if self
+            .last_saved
+            .map_or(true, |last_saved| last_saved.elapsed() >= SAVE_INTERVAL)
+        {
+            self.save()?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns true if there are entries that haven't been saved yet
+    pub fn is_unsaved(&self) -> bool {
+        self.unsaved
+    }
+
+    /// Saves the hall of fame to where it was loaded from, creating any missing directories
+    pub fn save(&mut self) -> io::Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let writer = BufWriter::new(File::create(&self.path)?);
+
+        serde_json::to_writer(writer, self).map_err(io::Error::from)?;
+
+        self.last_saved = Some(Instant::now());
+        self.unsaved = false;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{creature::CreatureBuilder, util::TestDirectory};
+
+    #[test]
+    pub fn hall_of_fame() {
+        let directory = TestDirectory::new("hall-of-fame");
+        let path = directory.path().join(FILE_NAME);
+
+        let mut hall_of_fame = HallOfFame::load(&path).unwrap();
+        let entry = |creature: &Creature, score: f32, generation: usize| {
+            HallOfFameEntry::new(
+                creature.clone(),
+                score,
+                score,
+                generation,
+                Uuid::nil(),
+                1,
+                EvolverSettings::default(),
+            )
+        };
+        let creatures: Vec<Creature> = (0..ALL_TIME_ENTRIES + 5)
+            .map(|_| CreatureBuilder::random().build())
+            .collect();
+
+        for (index, creature) in creatures.iter().enumerate() {
+            hall_of_fame
+                .record(entry(creature, index as f32, index + 1))
+                .unwrap();
+        }
+
+        // Being best again with a worse score doesn't add another all time entry
+        hall_of_fame
+            .record(entry(creatures.last().unwrap(), 0.0, creatures.len() + 1))
+            .unwrap();
+        hall_of_fame.save().unwrap();
+
+        let loaded = HallOfFame::load(&path).unwrap();
+        let all_time_scores: Vec<f32> = loaded.all_time().iter().map(|entry| entry.score).collect();
+
+        assert_eq!(loaded.generation_bests().len(), creatures.len() + 1);
+        assert_eq!(all_time_scores.len(), ALL_TIME_ENTRIES);
+        assert_eq!(all_time_scores[0], (creatures.len() - 1) as f32);
+        assert_eq!(
+            *all_time_scores.last().unwrap(),
+            (creatures.len() - ALL_TIME_ENTRIES) as f32
+        );
+        assert!(!loaded.is_unsaved());
+    }
+}
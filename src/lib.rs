@@ -1,15 +1,21 @@
 //! The library behind Project Evolution
 
+pub mod ancestry;
 pub mod creature;
 pub mod evolver;
+pub mod export;
+pub mod hall_of_fame;
+pub mod replay;
 pub mod res;
 pub mod simulation;
+pub mod statistics;
 pub mod ui;
 pub mod util;
 
 #[cfg(test)]
 mod tests {
     #[test]
+    #[allow(clippy::assertions_on_constants)]
     fn it_compiles() {
         assert!(true);
     }
@@ -1,7 +1,83 @@
-use project_evolution::ui;
+use std::{env, io, path::Path, process};
+
+use project_evolution::{creature::Creature, export, ui};
+
+const USAGE: &str = "Usage:
+    project-evolution
+        Opens the app
+    project-evolution export <creature or replay file> <output.gif, output.svg or output directory>
+        Exports a saved creature or replay as a GIF, an SVG of its body plan or a sequence of PNGs,
+        without opening a window
+    project-evolution evolve [--immigrants <count>] <creature files or directories>...
+        Opens the app and starts evolving from saved creatures, filling the rest of the first generation
+        with their mutants and the given number of random creatures";
+
 fn main() {
-    // Start
-    println!("Starting...");
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    match args.iter().map(String::as_str).collect::<Vec<_>>()[..] {
+        [] => {
+            // Start
+            println!("Starting...");
+
+            ui::init();
+        }
+        ["export", input, output] => {
+            println!("Exporting {} to {}...", input, output);
+
+            if let Err(error) = export::export_file(Path::new(input), Path::new(output)) {
+                eprintln!("Failed to export: {}", error);
+                process::exit(1);
+            }
+
+            println!("Done!");
+        }
+        ["evolve", ref arguments @ ..] if !arguments.is_empty() => {
+            let (random_immigrants, paths) = match arguments {
+                ["--immigrants", count, paths @ ..] => match count.parse() {
+                    Ok(count) => (count, paths),
+                    Err(_) => {
+                        eprintln!("{}", USAGE);
+                        process::exit(2);
+                    }
+                },
+                paths => (0, paths),
+            };
+
+            let templates = match load_templates(paths) {
+                Ok(templates) if !templates.is_empty() => templates,
+                Ok(_) => {
+                    eprintln!("No creatures found");
+                    process::exit(1);
+                }
+                Err(error) => {
+                    eprintln!("Failed to load creatures: {}", error);
+                    process::exit(1);
+                }
+            };
+
+            println!("Starting from {} creatures...", templates.len());
+
+            ui::init_with_templates(templates, random_immigrants);
+        }
+        _ => {
+            eprintln!("{}", USAGE);
+            process::exit(2);
+        }
+    }
+}
+
+/// Loads creatures from a list of creature files and directories of them
+fn load_templates(paths: &[&str]) -> io::Result<Vec<Creature>> {
+    let mut templates = Vec::new();
+
+    for path in paths.iter().map(Path::new) {
+        if path.is_dir() {
+            templates.extend(Creature::load_directory(path)?);
+        } else {
+            templates.push(Creature::load(path)?);
+        }
+    }
 
-    ui::init();
+    Ok(templates)
 }
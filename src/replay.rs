@@ -0,0 +1,368 @@
+//! Records [Simulation](crate::simulation::Simulation)s so they can be played back without re-running physics
+
+use std::{
+    fs::{self, File},
+    io::{self, BufReader, BufWriter},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use rapier::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    creature::{Creature, CREATURE_FILE_EXTENSION},
+    simulation::{Environment, SimulationView, Terrain, STEPS_FREQUENCY},
+};
+
+/// The extension of saved replay files
+pub const REPLAY_FILE_EXTENSION: &str = "replay.json";
+/// The number of steps between frames of the replays the evolver and exports record
+pub const REPLAY_STEPS_PER_FRAME: i32 = 2;
+
+/// The state of a creature's body at a single step
+#[derive(Clone, Serialize, Deserialize)]
+struct Frame {
+    positions: Vec<f32>, // x and y of each node, interleaved
+    extending: Vec<bool>,
+    score: f32,
+}
+
+/// Records frames of a [Simulation](crate::simulation::Simulation) as it runs
+pub struct Recorder {
+    node_ids: Vec<Uuid>,
+    muscle_ids: Vec<Uuid>,
+    steps_per_frame: i32,
+    frames: Vec<Frame>,
+}
+
+impl Recorder {
+    /// Creates a recorder for a [Creature] that keeps one frame every `steps_per_frame` steps
+    pub fn new(creature: &Creature, steps_per_frame: i32) -> Recorder {
+        Recorder {
+            node_ids: creature.nodes().keys().copied().collect(),
+            muscle_ids: creature.muscles().keys().copied().collect(),
+            steps_per_frame: steps_per_frame.max(1),
+            frames: Vec::new(),
+        }
+    }
+
+    /// Gets the number of steps between frames
+    pub fn steps_per_frame(&self) -> i32 {
+        self.steps_per_frame
+    }
+
+    /// Captures the current state of a simulation as a frame
+    pub fn capture(&mut self, view: &impl SimulationView) {
+        let positions = self
+            .node_ids
+            .iter()
+            .flat_map(|id| {
+                let position = view.get_position_of_node(*id);
+
+                [position.x, position.y]
+            })
+            .collect();
+
+        let extending = self
+            .muscle_ids
+            .iter()
+            .map(|id| view.is_muscle_extending(*id))
+            .collect();
+
+        self.frames.push(Frame {
+            positions,
+            extending,
+            score: view.get_score(),
+        });
+    }
+
+    /// Builds a [Replay] of everything recorded so far, in the [Environment] it was recorded in
+    pub fn to_replay(&self, creature: &Creature, environment: Environment) -> Replay {
+        Replay {
+            creature: creature.clone(),
+            environment,
+            node_ids: self.node_ids.clone(),
+            muscle_ids: self.muscle_ids.clone(),
+            steps_per_frame: self.steps_per_frame,
+            frames: self.frames.clone(),
+        }
+    }
+}
+
+/// A recording of a [Simulation](crate::simulation::Simulation), made by a [Recorder]
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Replay {
+    creature: Creature,
+    #[serde(default)]
+    environment: Environment,
+    node_ids: Vec<Uuid>,
+    muscle_ids: Vec<Uuid>,
+    steps_per_frame: i32,
+    frames: Vec<Frame>,
+}
+
+impl Replay {
+    /// Gets the [Creature] that was recorded
+    pub fn creature(&self) -> &Creature {
+        &self.creature
+    }
+
+    /// Gets the [Environment] the replay was recorded in
+    pub fn environment(&self) -> Environment {
+        self.environment
+    }
+
+    /// Gets the number of steps between frames
+    pub fn steps_per_frame(&self) -> i32 {
+        self.steps_per_frame
+    }
+
+    /// Gets the number of frames recorded
+    pub fn frame_count(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Gets how long the replay lasts when played back at normal speed
+    pub fn duration(&self) -> Duration {
+        self.frame_duration() * self.frames.len() as u32
+    }
+
+    /// Gets how long each frame lasts when played back at normal speed
+    pub fn frame_duration(&self) -> Duration {
+        STEPS_FREQUENCY * self.steps_per_frame as u32
+    }
+
+    /// Gets a frame by its index
+    ///
+    /// # Panics
+    /// If the index is out of bounds
+    pub fn frame(&self, index: usize) -> ReplayFrame<'_> {
+        ReplayFrame {
+            replay: self,
+            frame: &self.frames[index],
+        }
+    }
+
+    /// Gets the path to save the replay of a creature to, next to where the creature is saved
+    pub fn path_next_to(creature_path: &Path) -> PathBuf {
+        let file_name = creature_path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let stem = file_name
+            .strip_suffix(&format!(".{}", CREATURE_FILE_EXTENSION))
+            .unwrap_or(&file_name);
+
+        creature_path.with_file_name(format!("{}.{}", stem, REPLAY_FILE_EXTENSION))
+    }
+
+    /// Saves the replay to a file, creating any missing directories
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let writer = BufWriter::new(File::create(path)?);
+
+        serde_json::to_writer(writer, self).map_err(io::Error::from)
+    }
+
+    /// Loads a replay from a file
+    pub fn load(path: &Path) -> io::Result<Replay> {
+        let reader = BufReader::new(File::open(path)?);
+
+        serde_json::from_reader(reader).map_err(io::Error::from)
+    }
+}
+
+/// A single frame of a [Replay], which can be painted like a live simulation
+pub struct ReplayFrame<'a> {
+    replay: &'a Replay,
+    frame: &'a Frame,
+}
+
+impl SimulationView for ReplayFrame<'_> {
+    fn creature(&self) -> &Creature {
+        &self.replay.creature
+    }
+
+    fn get_position_of_node(&self, id: Uuid) -> Vector<f32> {
+        let index = self
+            .replay
+            .node_ids
+            .iter()
+            .position(|node_id| *node_id == id)
+            .unwrap();
+
+        vector![
+            self.frame.positions[index * 2],
+            self.frame.positions[index * 2 + 1]
+        ]
+    }
+
+    fn is_muscle_extending(&self, id: Uuid) -> bool {
+        let index = self
+            .replay
+            .muscle_ids
+            .iter()
+            .position(|muscle_id| *muscle_id == id)
+            .unwrap();
+
+        self.frame.extending[index]
+    }
+
+    fn get_score(&self) -> f32 {
+        self.frame.score
+    }
+
+    fn terrain(&self) -> Terrain {
+        self.replay.environment.terrain
+    }
+}
+
+/// Plays back a [Replay], allowing it to be paused, scrubbed and stepped through frame by frame
+pub struct ReplayPlayer {
+    replay: Replay,
+    frame: usize,
+    playing: bool,
+    time_left_over: Duration,
+}
+
+impl ReplayPlayer {
+    /// Creates a player at the start of a replay, already playing
+    pub fn new(replay: Replay) -> ReplayPlayer {
+        ReplayPlayer {
+            replay,
+            frame: 0,
+            playing: true,
+            time_left_over: Duration::ZERO,
+        }
+    }
+
+    /// Gets the [Replay] being played
+    pub fn replay(&self) -> &Replay {
+        &self.replay
+    }
+
+    /// Gets the index of the current frame
+    pub fn frame_index(&self) -> usize {
+        self.frame
+    }
+
+    /// Gets the current frame
+    pub fn current_frame(&self) -> ReplayFrame<'_> {
+        self.replay.frame(self.frame)
+    }
+
+    /// Jumps to a frame, clamping it to the replay
+    pub fn set_frame(&mut self, frame: usize) {
+        self.frame = frame.min(self.replay.frame_count().saturating_sub(1));
+        self.time_left_over = Duration::ZERO;
+    }
+
+    /// Moves forward one frame
+    pub fn step_forward(&mut self) {
+        self.set_frame(self.frame + 1);
+    }
+
+    /// Moves back one frame
+    pub fn step_backward(&mut self) {
+        self.set_frame(self.frame.saturating_sub(1));
+    }
+
+    /// Returns true if the replay is playing, false if paused
+    pub fn is_playing(&self) -> bool {
+        self.playing
+    }
+
+    /// Plays or pauses the replay. Playing from the last frame restarts it
+    pub fn set_playing(&mut self, playing: bool) {
+        if playing && self.is_finished() {
+            self.set_frame(0);
+        }
+
+        self.playing = playing;
+    }
+
+    /// Returns true if on the last frame
+    pub fn is_finished(&self) -> bool {
+        self.frame + 1 >= self.replay.frame_count()
+    }
+
+    /// Plays the replay for a certain amount of time, pausing at the end
+    pub fn run(&mut self, mut time: Duration) {
+        if !self.playing {
+            return;
+        }
+
+        time += self.time_left_over;
+
+        let frame_duration = self.replay.frame_duration();
+
+        while time > frame_duration && !self.is_finished() {
+            time -= frame_duration;
+            self.frame += 1;
+        }
+
+        if self.is_finished() {
+            self.playing = false;
+            time = Duration::ZERO;
+        }
+
+        self.time_left_over = time;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        creature::{CreatureBuilder, Position},
+        simulation::{Simulation, FLOOR_TOP_Y, WORLD_X_SIZE},
+        util::TestDirectory,
+    };
+
+    #[test]
+    pub fn record_and_replay() {
+        let creature = CreatureBuilder::random()
+            .translate_bottom_center_to(&Position::new(WORLD_X_SIZE / 2.0, FLOOR_TOP_Y))
+            .build();
+        let mut simulation = Simulation::new(creature);
+
+        simulation.start_recording(2);
+
+        for _ in 0..10 {
+            simulation.step();
+        }
+
+        let replay = simulation.replay().unwrap();
+        let last_frame = replay.frame(replay.frame_count() - 1);
+
+        assert_eq!(replay.frame_count(), 6);
+        assert_eq!(last_frame.get_score(), simulation.get_score());
+
+        for id in simulation.creature().nodes().keys() {
+            assert_eq!(
+                last_frame.get_position_of_node(*id),
+                simulation.get_position_of_node(*id)
+            );
+        }
+
+        let directory = TestDirectory::new("replay");
+        let creature_path = directory
+            .path()
+            .join(format!("replay.{}", CREATURE_FILE_EXTENSION));
+        let replay_path = Replay::path_next_to(&creature_path);
+
+        assert!(replay_path.ends_with(format!("replay.{}", REPLAY_FILE_EXTENSION)));
+
+        replay.save(&replay_path).unwrap();
+
+        let loaded = Replay::load(&replay_path).unwrap();
+
+        assert_eq!(loaded.frame_count(), replay.frame_count());
+        assert_eq!(loaded.creature().id(), replay.creature().id());
+    }
+}
@@ -1,11 +1,24 @@
 //! Manages the simulation of a [Creature]
 
-use std::{collections::HashMap, time::Duration};
+mod environment;
 
-use rapier::{na::Vector2, prelude::*};
+pub use environment::{Environment, Terrain, DEFAULT_GRAVITY};
+
+use std::{collections::HashMap, f32::consts::PI, time::Duration};
+
+use rapier::{
+    crossbeam::channel::{self, Receiver},
+    na::Vector2,
+    prelude::*,
+};
+use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use crate::{creature::Creature, util};
+use crate::{
+    creature::{Brain, Controller, Creature, Senses},
+    replay::{Recorder, Replay},
+    util,
+};
 
 pub const STEPS_PER_SECOND: i32 = 60;
 pub const STEPS_FREQUENCY: Duration = Duration::from_nanos(1_000_000_000 / STEPS_PER_SECOND as u64);
@@ -15,13 +28,15 @@ pub const FLOOR_HEIGHT: f32 = WORLD_Y_SIZE * 0.1;
 pub const FLOOR_TOP_Y: f32 = WORLD_Y_SIZE - FLOOR_HEIGHT;
 pub const SCORE_PER_SCREEN: i32 = 10; // The score range displayed on the screen (x segments)
 
-const GRAVITY: f32 = 200.0;
 const SCORE_SCALE_FACTOR: f32 = SCORE_PER_SCREEN as f32 / WORLD_X_SIZE;
 // Muscle extension and contraction range, where 0.0 is normal, -1.0 is maximum contraction, and 1.0 is double extension
 const MAX_MUSCLE_CONTRACTION: f32 = -0.5;
 const MAX_MUSCLE_EXTENSION: f32 = 0.5;
 const MUSCLE_LIMIT_FLUX: f32 = 1.15; // The percentage range muscles can go over max extension (1.15 = 15% over)
 const MUSCLE_STIFFNESS: f32 = 5.0; // How stiff the muscles are
+const ENERGY_SCALE_FACTOR: f32 = 1.0 / 1_000_000.0; // Scales raw motor work into readable energy units
+const EFFICIENCY_MIN_ENERGY: f32 = 1.0; // Added to the energy when computing efficiency, so barely moving isn't infinitely efficient
+const TERRAIN_AHEAD: f32 = WORLD_X_SIZE; // How far either side of the creature the terrain is built out to
 
 /// A simulation of a [Creature], using physics
 pub struct Simulation {
@@ -29,16 +44,35 @@ pub struct Simulation {
     physics_pipeline_parameters: PhysicsPipelineParameters,
     creature: Creature,
     node_id_to_rigid_body_handles: HashMap<Uuid, RigidBodyHandle>,
+    collider_handles_to_node_ids: HashMap<ColliderHandle, Uuid>,
+    node_id_to_contacts: HashMap<Uuid, NodeContact>,
+    event_collector: ChannelEventCollector,
+    collision_event_receiver: Receiver<CollisionEvent>,
     joint_handles_to_muscle_ids: HashMap<ImpulseJointHandle, Uuid>,
+    muscle_id_to_rest_angles: HashMap<Uuid, f32>,
+    muscle_id_to_extensions: HashMap<Uuid, f32>,
+    muscle_id_to_previous_extensions: HashMap<Uuid, f32>,
+    muscle_id_to_lengths: HashMap<Uuid, f32>,
+    muscle_id_to_energies: HashMap<Uuid, f32>,
+    brain_state: Vec<f32>,
+    recorder: Option<Recorder>,
     steps: i32,
+    environment: Environment,
+    /// The range of x the terrain has been built over, once it has been
+    terrain_built: Option<(f32, f32)>,
 }
 
 impl Simulation {
-    /// Creates a simulation of a [Creature]
+    /// Creates a simulation of a [Creature] in the default [Environment]
     pub fn new(creature: Creature) -> Simulation {
+        Self::with_environment(creature, Environment::default())
+    }
+
+    /// Creates a simulation of a [Creature] in an [Environment]
+    pub fn with_environment(creature: Creature, environment: Environment) -> Simulation {
         // Initialize pipeline params
         let mut physics_pipeline_parameters = PhysicsPipelineParameters {
-            gravity: vector![0.0, GRAVITY],
+            gravity: vector![0.0, environment.gravity],
             integration_parameters: IntegrationParameters::default(),
             islands: IslandManager::new(),
             broad_phase: BroadPhase::new(),
@@ -74,10 +108,12 @@ impl Simulation {
         let muscle_id_to_movement_parameters = creature.movement_parameters();
 
         let mut node_id_to_rigid_body_handles = HashMap::new();
+        let mut collider_handles_to_node_ids = HashMap::new();
         let mut joint_handles_to_muscle_ids = HashMap::new();
+        let mut muscle_id_to_rest_angles = HashMap::new();
 
-        // Add node rigid bodies
-        for node in nodes.values() {
+        // Add node rigid bodies, in order so the physics plays out the same way every run
+        for (_, node) in util::sorted_by_id(nodes) {
             let body = RigidBodyBuilder::dynamic()
                 .translation(vector![node.position.x, node.position.y])
                 .build();
@@ -91,13 +127,16 @@ impl Simulation {
                     filter: Group::GROUP_1,
                 })
                 .restitution(0.7)
+                .active_events(ActiveEvents::COLLISION_EVENTS)
                 .build();
 
-            collider_set.insert_with_parent(collider, body_handle, rigid_body_set);
+            let collider_handle =
+                collider_set.insert_with_parent(collider, body_handle, rigid_body_set);
+            collider_handles_to_node_ids.insert(collider_handle, node.id);
         }
 
         // Add muscle joints
-        for (id, muscle) in muscles {
+        for (id, muscle) in util::sorted_by_id(muscles) {
             let from_node_position = &nodes.get(&muscle.from_id).unwrap().position;
             let to_node_position = &nodes.get(&muscle.to_id).unwrap().position;
             let from_node_body_handle = node_id_to_rigid_body_handles.get(&muscle.from_id).unwrap();
@@ -168,19 +207,63 @@ impl Simulation {
                 impulse_joint_set.insert(*from_node_body_handle, *to_node_body_handle, joint, true);
 
             joint_handles_to_muscle_ids.insert(joint_handle, *id);
+            muscle_id_to_rest_angles.insert(*id, f32::atan2(offset.y, offset.x));
         }
 
         // Build simulation
         let physics_pipeline = PhysicsPipeline::new();
 
-        Simulation {
+        let (collision_event_sender, collision_event_receiver) = channel::unbounded();
+        let (contact_force_event_sender, _) = channel::unbounded();
+        let event_collector =
+            ChannelEventCollector::new(collision_event_sender, contact_force_event_sender);
+
+        let muscle_id_to_lengths = muscle_id_to_movement_parameters
+            .iter()
+            .map(|(id, movement_parameters)| (*id, movement_parameters.muscle_length()))
+            .collect();
+        let muscle_id_to_energies = muscles.keys().map(|id| (*id, 0.0)).collect();
+
+        let node_id_to_contacts = nodes
+            .keys()
+            .map(|id| (*id, NodeContact::default()))
+            .collect();
+
+        let brain_state = match creature.controller() {
+            Controller::Timed => Vec::new(),
+            Controller::Neural(brain) => brain.new_state(),
+        };
+
+        let mut simulation = Simulation {
             physics_pipeline,
             physics_pipeline_parameters,
             creature,
             node_id_to_rigid_body_handles,
+            collider_handles_to_node_ids,
+            node_id_to_contacts,
+            event_collector,
+            collision_event_receiver,
             joint_handles_to_muscle_ids,
+            muscle_id_to_rest_angles,
+            muscle_id_to_extensions: HashMap::new(),
+            muscle_id_to_previous_extensions: HashMap::new(),
+            muscle_id_to_lengths,
+            muscle_id_to_energies,
+            brain_state,
+            recorder: None,
             steps: 0,
-        }
+            environment,
+            terrain_built: None,
+        };
+
+        simulation.build_terrain();
+
+        simulation
+    }
+
+    /// Gets the [Environment] the simulation takes place in
+    pub fn environment(&self) -> Environment {
+        self.environment
     }
 
     /// Gets the [Creature] being simulated
@@ -198,13 +281,85 @@ impl Simulation {
             .translation()
     }
 
-    /// Gets the extension delta of a node by it's id
+    /// Returns true if the muscle with this id is extending, false if contracting
     pub fn is_muscle_extending(&self, id: Uuid) -> bool {
-        self.creature
-            .movement_parameters()
-            .get(&id)
-            .unwrap()
-            .is_extending(self.steps)
+        match self.creature.controller() {
+            Controller::Timed => self
+                .creature
+                .movement_parameters()
+                .get(&id)
+                .unwrap()
+                .is_extending(self.steps),
+            Controller::Neural(_) => {
+                let extension = self.muscle_id_to_extensions.get(&id).unwrap_or(&0.5);
+                let previous_extension = self
+                    .muscle_id_to_previous_extensions
+                    .get(&id)
+                    .unwrap_or(&0.5);
+
+                extension >= previous_extension
+            }
+        }
+    }
+
+    /// Gets the ground [contacts](NodeContact) of every node, keyed by their id
+    pub fn node_contacts(&self) -> &HashMap<Uuid, NodeContact> {
+        &self.node_id_to_contacts
+    }
+
+    /// Returns true if the node with this id is touching the ground
+    pub fn is_node_touching_ground(&self, id: Uuid) -> bool {
+        self.node_id_to_contacts.get(&id).unwrap().is_touching()
+    }
+
+    /// Gets how far the [Creature] has rotated from its starting orientation, in half turns,
+    /// by averaging how far each muscle has rotated
+    pub fn get_tilt(&self) -> f32 {
+        let muscles = self.creature.muscles();
+
+        if muscles.is_empty() {
+            return 0.0;
+        }
+
+        let total_rotation: f32 = muscles
+            .iter()
+            .map(|(id, muscle)| {
+                let from = self.get_position_of_node(muscle.from_id);
+                let to = self.get_position_of_node(muscle.to_id);
+                let angle = f32::atan2(to.y - from.y, to.x - from.x);
+
+                (angle - self.muscle_id_to_rest_angles[id] + PI).rem_euclid(2.0 * PI) - PI
+            })
+            .sum();
+
+        total_rotation / muscles.len() as f32 / PI
+    }
+
+    /// Gathers what a [Brain] can sense about the [Creature]'s body
+    fn sense(&self, brain: &Brain) -> Senses {
+        let movement_parameters = self.creature.movement_parameters();
+
+        Senses {
+            ground_contacts: brain
+                .node_ids()
+                .iter()
+                .map(|id| self.is_node_touching_ground(*id))
+                .collect(),
+            muscle_lengths: brain
+                .muscle_ids()
+                .iter()
+                .map(|id| {
+                    let muscle = &self.creature.muscles()[id];
+                    let length = util::distance(
+                        &self.get_position_of_node(muscle.from_id),
+                        &self.get_position_of_node(muscle.to_id),
+                    );
+
+                    length / movement_parameters[id].muscle_length() - 1.0
+                })
+                .collect(),
+            tilt: self.get_tilt(),
+        }
     }
 
     /// Gets the bounds of the [Creature] in the form (top_left, bottom_right)
@@ -250,8 +405,60 @@ impl Simulation {
         Self::x_to_score(bottom_right.x)
     }
 
+    /// Gets the number of steps simulated so far
+    pub fn steps(&self) -> i32 {
+        self.steps
+    }
+
+    /// Gets the energy each muscle has spent so far, keyed by their id
+    pub fn muscle_energies(&self) -> &HashMap<Uuid, f32> {
+        &self.muscle_id_to_energies
+    }
+
+    /// Gets the total energy the [Creature]'s muscles have spent so far
+    pub fn get_total_energy(&self) -> f32 {
+        // Summed in a fixed order, so reruns of a creature come out exactly the same
+        util::sorted_by_id(&self.muscle_id_to_energies)
+            .into_iter()
+            .map(|(_, energy)| energy)
+            .sum()
+    }
+
+    /// Gets the score per unit of energy spent, which rewards creatures that move without wasting energy. Going
+    /// backwards counts as not moving, so spending energy never makes up for it
+    pub fn get_efficiency(&self) -> f32 {
+        self.get_score().max(0.0) / (self.get_total_energy() + EFFICIENCY_MIN_ENERGY)
+    }
+
+    /// Gets the fitness of this simulation, as measured by `fitness`
+    pub fn get_fitness(&self, fitness: Fitness) -> f32 {
+        match fitness {
+            Fitness::Distance => self.get_score(),
+            Fitness::Efficiency => self.get_efficiency(),
+        }
+    }
+
     /// Steps the muscles one step forward in time
     fn step_muscles(&mut self) {
+        let extensions = match self.creature.controller() {
+            Controller::Timed => self
+                .creature
+                .movement_parameters()
+                .iter()
+                .map(|(id, movement_parameters)| {
+                    (*id, movement_parameters.get_extension_at(self.steps))
+                })
+                .collect(),
+            Controller::Neural(brain) => {
+                let senses = self.sense(brain);
+
+                brain.think(self.steps, &senses, &mut self.brain_state)
+            }
+        };
+
+        self.muscle_id_to_previous_extensions =
+            std::mem::replace(&mut self.muscle_id_to_extensions, extensions);
+
         let physics_parameters = &mut self.physics_pipeline_parameters;
 
         for (handle, joint) in physics_parameters.impulse_joint_set.iter_mut() {
@@ -260,7 +467,7 @@ impl Simulation {
                     self.creature.movement_parameters().get(muscle_id).unwrap();
                 let muscle_length = movement_parameters.muscle_length();
 
-                let extension_delta = movement_parameters.get_extension_at(self.steps);
+                let extension_delta = self.muscle_id_to_extensions[muscle_id];
                 let extension = MAX_MUSCLE_CONTRACTION
                     + (MAX_MUSCLE_EXTENSION - MAX_MUSCLE_CONTRACTION) * extension_delta;
 
@@ -277,7 +484,7 @@ impl Simulation {
         let params = &mut self.physics_pipeline_parameters;
 
         let physics_hooks = &();
-        let events_handler = &();
+        let events_handler = &self.event_collector;
 
         self.physics_pipeline.step(
             &params.gravity,
@@ -293,7 +500,305 @@ impl Simulation {
             physics_hooks,
             events_handler,
         );
+        self.step_contacts();
+        self.step_energies();
+        self.build_terrain();
         self.steps += 1;
+
+        if let Some(mut recorder) = self.recorder.take() {
+            if self.steps % recorder.steps_per_frame() == 0 {
+                recorder.capture(self);
+            }
+
+            self.recorder = Some(recorder);
+        }
+    }
+
+    /// Starts recording the simulation from the current step, keeping one frame every `steps_per_frame` steps
+    pub fn start_recording(&mut self, steps_per_frame: i32) {
+        let mut recorder = Recorder::new(&self.creature, steps_per_frame);
+
+        recorder.capture(self);
+
+        self.recorder = Some(recorder);
+    }
+
+    /// Gets a [Replay] of everything recorded so far, if recording
+    pub fn replay(&self) -> Option<Replay> {
+        self.recorder
+            .as_ref()
+            .map(|recorder| recorder.to_replay(&self.creature, self.environment))
+    }
+
+    /// Builds the terrain out to [TERRAIN_AHEAD] either side of the creature, once it's come within half that
+    /// of where the terrain has been built to. Only what the creature can reach ever gets built
+    fn build_terrain(&mut self) {
+        if self.creature.nodes().is_empty() {
+            return;
+        }
+
+        let (top_left, bottom_right) = self.get_bounds();
+        let wanted = (top_left.x - TERRAIN_AHEAD, bottom_right.x + TERRAIN_AHEAD);
+        let terrain = self.environment.terrain;
+
+        let (built_min, built_max) = match self.terrain_built {
+            Some(built) => built,
+            None => {
+                for obstacle in terrain.obstacles_between(wanted.0, wanted.1) {
+                    self.add_obstacle(obstacle);
+                }
+
+                self.terrain_built = Some(wanted);
+
+                return;
+            }
+        };
+
+        // Obstacles reaching into what's already built were added along with it
+        if top_left.x - TERRAIN_AHEAD / 2.0 < built_min {
+            for obstacle in terrain.obstacles_between(wanted.0, built_min) {
+                if obstacle.1.x < built_min {
+                    self.add_obstacle(obstacle);
+                }
+            }
+        }
+
+        if bottom_right.x + TERRAIN_AHEAD / 2.0 > built_max {
+            for obstacle in terrain.obstacles_between(built_max, wanted.1) {
+                if obstacle.0.x > built_max {
+                    self.add_obstacle(obstacle);
+                }
+            }
+        }
+
+        self.terrain_built = Some((built_min.min(wanted.0), built_max.max(wanted.1)));
+    }
+
+    /// Adds a block of terrain in the form (top_left, bottom_right), which collides like the floor
+    fn add_obstacle(&mut self, (top_left, bottom_right): (Vector<f32>, Vector<f32>)) {
+        let params = &mut self.physics_pipeline_parameters;
+        let half_size = (bottom_right - top_left) / 2.0;
+        let obstacle = RigidBodyBuilder::fixed()
+            .translation(top_left + half_size)
+            .build();
+        let obstacle_handle = params.rigid_body_set.insert(obstacle);
+
+        let obstacle_collider = ColliderBuilder::cuboid(half_size.x, half_size.y)
+            .collision_groups(InteractionGroups {
+                memberships: Group::GROUP_1,
+                filter: Group::ALL,
+            })
+            .build();
+
+        params.collider_set.insert_with_parent(
+            obstacle_collider,
+            obstacle_handle,
+            &mut params.rigid_body_set,
+        );
+    }
+
+    /// Adds the work each muscle's motor did in the last step to its energy
+    fn step_energies(&mut self) {
+        let dt = self.physics_pipeline_parameters.integration_parameters.dt;
+
+        for (handle, joint) in self.physics_pipeline_parameters.impulse_joint_set.iter() {
+            if let Some(muscle_id) = self.joint_handles_to_muscle_ids.get(&handle) {
+                let muscle = &self.creature.muscles()[muscle_id];
+                let length = util::distance(
+                    &self.get_position_of_node(muscle.from_id),
+                    &self.get_position_of_node(muscle.to_id),
+                );
+                let previous_length = self
+                    .muscle_id_to_lengths
+                    .insert(*muscle_id, length)
+                    .unwrap();
+
+                let impulse = joint
+                    .data
+                    .as_prismatic()
+                    .and_then(|prismatic| prismatic.motor())
+                    .map_or(0.0, |motor| motor.impulse);
+
+                // Work = force * displacement, where force = impulse / dt
+                let work =
+                    (impulse / dt).abs() * (length - previous_length).abs() * ENERGY_SCALE_FACTOR;
+
+                *self.muscle_id_to_energies.get_mut(muscle_id).unwrap() += work;
+            }
+        }
+    }
+
+    /// Updates the node contacts from the collision events of the last step
+    fn step_contacts(&mut self) {
+        while let Ok(event) = self.collision_event_receiver.try_recv() {
+            for collider_handle in [event.collider1(), event.collider2()] {
+                if let Some(node_id) = self.collider_handles_to_node_ids.get(&collider_handle) {
+                    let contact = self.node_id_to_contacts.get_mut(node_id).unwrap();
+
+                    if event.started() {
+                        contact.start();
+                    } else {
+                        contact.stop();
+                    }
+                }
+            }
+        }
+
+        for contact in self.node_id_to_contacts.values_mut() {
+            if contact.is_touching() {
+                contact.steps_on_ground += 1;
+            }
+        }
+    }
+}
+
+/// A read-only view of a simulated [Creature] at a single point in time, which is everything needed to paint it
+pub trait SimulationView {
+    /// Gets the [Creature] being simulated
+    fn creature(&self) -> &Creature;
+
+    /// Gets the position of the node by it's id
+    fn get_position_of_node(&self, id: Uuid) -> Vector<f32>;
+
+    /// Returns true if the muscle with this id is extending, false if contracting
+    fn is_muscle_extending(&self, id: Uuid) -> bool;
+
+    /// Gets the score (furthest x distance)
+    fn get_score(&self) -> f32;
+
+    /// Gets the shape of the ground the creature is on
+    fn terrain(&self) -> Terrain;
+
+    /// Gets the bounds of the [Creature] in the form (top_left, bottom_right)
+    fn get_bounds(&self) -> (Vector2<f32>, Vector2<f32>) {
+        let positions = self
+            .creature()
+            .nodes()
+            .keys()
+            .map(|id| self.get_position_of_node(*id));
+        let x_pos_iter = positions.clone().map(|position| position.x);
+        let y_pos_iter = positions.map(|position| position.y);
+
+        let x_min = x_pos_iter.clone().min_by(util::cmp_f32).unwrap();
+        let y_min = y_pos_iter.clone().min_by(util::cmp_f32).unwrap();
+        let x_max = x_pos_iter.max_by(util::cmp_f32).unwrap();
+        let y_max = y_pos_iter.max_by(util::cmp_f32).unwrap();
+
+        (Vector2::new(x_min, y_min), Vector2::new(x_max, y_max))
+    }
+
+    /// Gets the distance from a position to the edge of the closest node, which is 0 inside a node
+    fn get_distance_to_closest_node(&self, position: &Vector<f32>) -> f32 {
+        self.creature()
+            .nodes()
+            .values()
+            .map(|node| {
+                let distance = util::distance(&self.get_position_of_node(node.id), position);
+
+                (distance - node.size / 2.0).max(0.0)
+            })
+            .min_by(util::cmp_f32)
+            .unwrap_or(f32::INFINITY)
+    }
+
+    /// Gets the lowest position to safely display text above
+    fn get_text_position(&self) -> Vector2<f32> {
+        let (top_left, bottom_right) = self.get_bounds();
+
+        Vector2::new((top_left.x + bottom_right.x) / 2.0, top_left.y)
+    }
+}
+
+impl SimulationView for Simulation {
+    fn creature(&self) -> &Creature {
+        Simulation::creature(self)
+    }
+
+    fn get_position_of_node(&self, id: Uuid) -> Vector<f32> {
+        Simulation::get_position_of_node(self, id)
+    }
+
+    fn is_muscle_extending(&self, id: Uuid) -> bool {
+        Simulation::is_muscle_extending(self, id)
+    }
+
+    fn get_score(&self) -> f32 {
+        Simulation::get_score(self)
+    }
+
+    fn terrain(&self) -> Terrain {
+        self.environment.terrain
+    }
+
+    fn get_bounds(&self) -> (Vector2<f32>, Vector2<f32>) {
+        Simulation::get_bounds(self)
+    }
+}
+
+/// What a [Simulation] is judged by when comparing creatures
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Fitness {
+    /// The score, see [Simulation::get_score]
+    #[default]
+    Distance,
+    /// The score per unit of energy, see [Simulation::get_efficiency]
+    Efficiency,
+}
+
+impl Fitness {
+    /// Every fitness, in the order they're shown
+    pub const ALL: [Fitness; 2] = [Fitness::Distance, Fitness::Efficiency];
+
+    /// The display name of the fitness
+    pub fn name(&self) -> &'static str {
+        match self {
+            Fitness::Distance => "Distance",
+            Fitness::Efficiency => "Distance per energy",
+        }
+    }
+}
+
+/// The ground contact state and history of a node in a [Simulation]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NodeContact {
+    active_contacts: u32,
+    contact_count: u32,
+    steps_on_ground: i32,
+}
+
+impl NodeContact {
+    /// Returns true if the node is currently touching the ground
+    pub fn is_touching(&self) -> bool {
+        self.active_contacts > 0
+    }
+
+    /// Gets the number of times the node has touched down on the ground
+    pub fn contact_count(&self) -> u32 {
+        self.contact_count
+    }
+
+    /// Gets the number of steps the node has spent on the ground
+    pub fn steps_on_ground(&self) -> i32 {
+        self.steps_on_ground
+    }
+
+    /// Gets the time the node has spent on the ground, in seconds
+    pub fn time_on_ground(&self) -> f32 {
+        self.steps_on_ground as f32 / STEPS_PER_SECOND as f32
+    }
+
+    /// Records a collision starting
+    fn start(&mut self) {
+        if self.active_contacts == 0 {
+            self.contact_count += 1;
+        }
+
+        self.active_contacts += 1;
+    }
+
+    /// Records a collision stopping
+    fn stop(&mut self) {
+        self.active_contacts = self.active_contacts.saturating_sub(1);
     }
 }
 
@@ -310,3 +815,117 @@ struct PhysicsPipelineParameters {
     multibody_joints_set: MultibodyJointSet,
     ccd_solver: CCDSolver,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::creature::{CreatureBuilder, MovementParameters, Muscle, Node, Position, Waveform};
+
+    /// Builds two nodes on the floor joined by a muscle that moves with a waveform
+    fn muscle_pair(waveform: Waveform) -> Creature {
+        let from = Node::new(Position::new(WORLD_X_SIZE / 2.0, FLOOR_TOP_Y - 10.0), 10.0);
+        let to = Node::new(
+            Position::new(WORLD_X_SIZE / 2.0 + 60.0, FLOOR_TOP_Y - 10.0),
+            10.0,
+        );
+        let muscle = Muscle::new(from.id, to.id);
+        let muscle_id = muscle.id;
+        let period = STEPS_PER_SECOND / 2;
+        let movement_parameters = MovementParameters::new(60.0, period, period, 0, waveform);
+
+        CreatureBuilder::new()
+            .add_node(from)
+            .add_node(to)
+            .add_muscle(muscle)
+            .add_movement_parameters(HashMap::from([(muscle_id, movement_parameters)]))
+            .build()
+    }
+
+    #[test]
+    pub fn node_contacts() {
+        let node = Node::new(Position::new(WORLD_X_SIZE / 2.0, FLOOR_TOP_Y - 50.0), 10.0);
+        let id = node.id;
+        let mut simulation = Simulation::new(CreatureBuilder::new().add_node(node).build());
+
+        assert!(!simulation.is_node_touching_ground(id));
+
+        for _ in 0..STEPS_PER_SECOND * 5 {
+            simulation.step();
+        }
+
+        let contact = simulation.node_contacts()[&id];
+
+        assert!(contact.is_touching());
+        assert!(contact.contact_count() >= 1);
+        assert!(contact.time_on_ground() > 0.0);
+    }
+
+    #[test]
+    pub fn distance_to_closest_node() {
+        let node = Node::new(Position::new(100.0, 100.0), 10.0);
+        let simulation = Simulation::new(CreatureBuilder::new().add_node(node).build());
+
+        assert_eq!(
+            simulation.get_distance_to_closest_node(&Vector::new(102.0, 100.0)),
+            0.0
+        );
+        assert!(
+            (simulation.get_distance_to_closest_node(&Vector::new(100.0, 115.0)) - 10.0).abs()
+                < 0.001
+        );
+    }
+
+    #[test]
+    pub fn muscle_energies() {
+        let mut simulation = Simulation::new(muscle_pair(Waveform::Square));
+        let mut last_total = 0.0;
+
+        for second in 1..=3 {
+            for _ in 0..STEPS_PER_SECOND {
+                simulation.step();
+            }
+
+            let total = simulation.get_total_energy();
+
+            assert!(simulation
+                .muscle_energies()
+                .values()
+                .all(|energy| *energy >= 0.0));
+            assert!(total > last_total, "No energy spent in second {}", second);
+
+            last_total = total;
+        }
+
+        // Holding the muscle at its normal length costs next to nothing
+        let mut idle = Simulation::new(muscle_pair(Waveform::Keyframes(vec![0.5, 0.5])));
+
+        for _ in 0..STEPS_PER_SECOND * 3 {
+            idle.step();
+        }
+
+        assert!(idle.get_total_energy() < last_total * 0.01);
+        assert!(idle.get_efficiency() >= 0.0);
+    }
+
+    #[test]
+    pub fn terrain_is_built_nearby() {
+        let node = Node::new(Position::new(WORLD_X_SIZE / 2.0, FLOOR_TOP_Y - 50.0), 10.0);
+        let environment = Environment {
+            terrain: Terrain::Hurdles,
+            ..Default::default()
+        };
+        let simulation = Simulation::with_environment(
+            CreatureBuilder::new().add_node(node).build(),
+            environment,
+        );
+        // Everything but the floor and the node
+        let obstacles = simulation.physics_pipeline_parameters.collider_set.len() - 2;
+        let nearby = Terrain::Hurdles.obstacles_between(
+            WORLD_X_SIZE / 2.0 - TERRAIN_AHEAD,
+            WORLD_X_SIZE / 2.0 + TERRAIN_AHEAD,
+        );
+
+        assert_eq!(obstacles, nearby.len());
+        assert!(obstacles < Terrain::Hurdles.obstacles().len());
+    }
+}
@@ -0,0 +1,108 @@
+use rapier::prelude::Vector;
+use serde::{Deserialize, Serialize};
+
+use super::{FLOOR_TOP_Y, WORLD_X_SIZE};
+
+/// The default strength of gravity, in world units per second squared
+pub const DEFAULT_GRAVITY: f32 = 200.0;
+
+const START_X: f32 = WORLD_X_SIZE / 2.0; // Where creatures are placed at the start
+const OBSTACLE_START_DISTANCE: f32 = 200.0; // How far from the start the first obstacle is
+const OBSTACLE_END_DISTANCE: f32 = WORLD_X_SIZE * 20.0; // How far from the start the obstacles stop
+const HURDLE_SPACING: f32 = 150.0;
+const HURDLE_WIDTH: f32 = 10.0;
+const HURDLE_HEIGHT: f32 = 15.0;
+const STAIR_WIDTH: f32 = 200.0;
+const STAIR_HEIGHT: f32 = 8.0;
+const MAX_STAIRS: usize = 25; // The last stair carries on to the end
+
+/// The world a [Simulation](super::Simulation) takes place in
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Environment {
+    /// How strongly everything is pulled down, in world units per second squared
+    pub gravity: f32,
+    pub terrain: Terrain,
+}
+
+impl Default for Environment {
+    fn default() -> Self {
+        Environment {
+            gravity: DEFAULT_GRAVITY,
+            terrain: Terrain::default(),
+        }
+    }
+}
+
+/// The shape of the ground
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Terrain {
+    /// Nothing but the floor
+    #[default]
+    Flat,
+    /// Small blocks spaced out evenly in both directions
+    Hurdles,
+    /// Stairs going up to the right
+    Stairs,
+}
+
+impl Terrain {
+    /// Every terrain, in the order they're shown
+    pub const ALL: [Terrain; 3] = [Terrain::Flat, Terrain::Hurdles, Terrain::Stairs];
+
+    /// The display name of the terrain
+    pub fn name(&self) -> &'static str {
+        match self {
+            Terrain::Flat => "Flat",
+            Terrain::Hurdles => "Hurdles",
+            Terrain::Stairs => "Stairs",
+        }
+    }
+
+    /// Gets the blocks sitting on the floor, in the form (top_left, bottom_right)
+    pub fn obstacles(&self) -> Vec<(Vector<f32>, Vector<f32>)> {
+        let block = |min_x: f32, max_x: f32, height: f32| {
+            (
+                Vector::new(min_x, FLOOR_TOP_Y - height),
+                Vector::new(max_x, FLOOR_TOP_Y),
+            )
+        };
+
+        match self {
+            Terrain::Flat => Vec::new(),
+            Terrain::Hurdles => {
+                let count =
+                    ((OBSTACLE_END_DISTANCE - OBSTACLE_START_DISTANCE) / HURDLE_SPACING) as usize;
+
+                (0..count)
+                    .flat_map(|index| {
+                        let distance = OBSTACLE_START_DISTANCE + index as f32 * HURDLE_SPACING;
+
+                        [START_X + distance, START_X - distance - HURDLE_WIDTH]
+                    })
+                    .map(|x| block(x, x + HURDLE_WIDTH, HURDLE_HEIGHT))
+                    .collect()
+            }
+            Terrain::Stairs => (0..MAX_STAIRS)
+                .map(|index| {
+                    let min_x = START_X + OBSTACLE_START_DISTANCE + index as f32 * STAIR_WIDTH;
+                    let max_x = if index + 1 == MAX_STAIRS {
+                        START_X + OBSTACLE_END_DISTANCE
+                    } else {
+                        min_x + STAIR_WIDTH
+                    };
+
+                    block(min_x, max_x, (index + 1) as f32 * STAIR_HEIGHT)
+                })
+                .collect(),
+        }
+    }
+
+    /// Gets the blocks sitting on the floor that reach between two x positions, like [Terrain::obstacles]
+    pub fn obstacles_between(&self, min_x: f32, max_x: f32) -> Vec<(Vector<f32>, Vector<f32>)> {
+        let mut obstacles = self.obstacles();
+
+        obstacles.retain(|(top_left, bottom_right)| bottom_right.x >= min_x && top_left.x <= max_x);
+
+        obstacles
+    }
+}
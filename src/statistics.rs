@@ -0,0 +1,211 @@
+//! Summarizes generations and exports the summaries for plotting in external tools
+
+use std::{
+    fs::{self, File},
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::util;
+
+/// The directory statistics are exported to by default, relative to the working directory
+pub const STATISTICS_DIRECTORY: &str = "statistics";
+const CSV_HEADER: &str = "generation,best,p90,p75,median,p25,p10,worst,mean,standard_deviation,average_nodes,average_muscles,wall_time_seconds";
+
+/// A summary of the scores and creatures of a single generation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GenerationStatistics {
+    pub generation: usize,
+    pub best: f32,
+    pub p90: f32,
+    pub p75: f32,
+    pub median: f32,
+    pub p25: f32,
+    pub p10: f32,
+    pub worst: f32,
+    pub mean: f32,
+    pub standard_deviation: f32,
+    pub average_nodes: f32,
+    pub average_muscles: f32,
+    pub wall_time_seconds: f32,
+}
+
+impl GenerationStatistics {
+    /// Summarizes a generation from its scores, in any order
+    ///
+    /// # Panics
+    /// If there are no scores, or a score is NAN
+    pub fn new(
+        generation: usize,
+        scores: &[f32],
+        average_nodes: f32,
+        average_muscles: f32,
+        wall_time: Duration,
+    ) -> GenerationStatistics {
+        let mut sorted_scores = scores.to_vec();
+        sorted_scores.sort_by(util::cmp_f32);
+
+        let count = sorted_scores.len() as f32;
+        let mean = sorted_scores.iter().sum::<f32>() / count;
+        let variance = sorted_scores
+            .iter()
+            .map(|score| (score - mean).powi(2))
+            .sum::<f32>()
+            / count;
+
+        GenerationStatistics {
+            generation,
+            best: *sorted_scores.last().unwrap(),
+            p90: percentile(&sorted_scores, 0.9),
+            p75: percentile(&sorted_scores, 0.75),
+            median: percentile(&sorted_scores, 0.5),
+            p25: percentile(&sorted_scores, 0.25),
+            p10: percentile(&sorted_scores, 0.1),
+            worst: sorted_scores[0],
+            mean,
+            standard_deviation: variance.sqrt(),
+            average_nodes,
+            average_muscles,
+            wall_time_seconds: wall_time.as_secs_f32(),
+        }
+    }
+
+    /// Formats the statistics as a CSV row, matching [CSV_HEADER]
+    fn to_csv_row(&self) -> String {
+        format!(
+            "{},{},{},{},{},{},{},{},{},{},{},{},{}",
+            self.generation,
+            self.best,
+            self.p90,
+            self.p75,
+            self.median,
+            self.p25,
+            self.p10,
+            self.worst,
+            self.mean,
+            self.standard_deviation,
+            self.average_nodes,
+            self.average_muscles,
+            self.wall_time_seconds
+        )
+    }
+}
+
+/// Gets a percentile (in [0, 1]) of sorted values, interpolating between the closest two
+///
+/// # Panics
+/// If there are no values
+pub fn percentile(sorted_values: &[f32], percentile: f32) -> f32 {
+    let index = percentile * (sorted_values.len() - 1) as f32;
+    let lower = index.floor() as usize;
+    let upper = index.ceil() as usize;
+
+    sorted_values[lower] + (sorted_values[upper] - sorted_values[lower]) * index.fract()
+}
+
+/// The file format statistics are exported in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatisticsFormat {
+    /// Comma separated values, with a header row
+    Csv,
+    /// One JSON object per line
+    JsonLines,
+}
+
+impl StatisticsFormat {
+    /// Picks the format from a path's extension, using JSON Lines for `.jsonl` and `.json` files and CSV otherwise
+    pub fn from_path(path: &Path) -> StatisticsFormat {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("jsonl") | Some("json") => StatisticsFormat::JsonLines,
+            _ => StatisticsFormat::Csv,
+        }
+    }
+
+    /// The extension files in this format use
+    pub fn extension(&self) -> &'static str {
+        match self {
+            StatisticsFormat::Csv => "csv",
+            StatisticsFormat::JsonLines => "jsonl",
+        }
+    }
+
+    /// Gets a path for a new run's statistics inside [STATISTICS_DIRECTORY], named after the current time
+    pub fn default_path(&self) -> PathBuf {
+        let seconds = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+
+        Path::new(STATISTICS_DIRECTORY).join(format!("run-{}.{}", seconds, self.extension()))
+    }
+}
+
+/// Writes [GenerationStatistics] to a file one row at a time, flushing after each so the file can be read
+/// while the run progresses
+pub struct StatisticsExporter {
+    writer: BufWriter<File>,
+    format: StatisticsFormat,
+}
+
+impl StatisticsExporter {
+    /// Creates (or overwrites) a statistics file, creating any missing directories
+    pub fn create(path: &Path, format: StatisticsFormat) -> io::Result<StatisticsExporter> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let mut writer = BufWriter::new(File::create(path)?);
+
+        if format == StatisticsFormat::Csv {
+            writeln!(writer, "{}", CSV_HEADER)?;
+            writer.flush()?;
+        }
+
+        Ok(StatisticsExporter { writer, format })
+    }
+
+    /// Writes the statistics of a generation as a row
+    pub fn write(&mut self, statistics: &GenerationStatistics) -> io::Result<()> {
+        match self.format {
+            StatisticsFormat::Csv => writeln!(self.writer, "{}", statistics.to_csv_row())?,
+            StatisticsFormat::JsonLines => {
+                serde_json::to_writer(&mut self.writer, statistics)?;
+                writeln!(self.writer)?;
+            }
+        }
+
+        self.writer.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    pub fn generation_statistics() {
+        let statistics = GenerationStatistics::new(
+            3,
+            &[4.0, 1.0, 3.0, 2.0, 5.0],
+            3.5,
+            4.0,
+            Duration::from_secs(2),
+        );
+
+        assert_eq!(statistics.best, 5.0);
+        assert_eq!(statistics.worst, 1.0);
+        assert_eq!(statistics.median, 3.0);
+        assert_eq!(statistics.mean, 3.0);
+        assert_eq!(statistics.p25, 2.0);
+        assert!((statistics.p90 - 4.6).abs() < 0.001);
+        assert_eq!(statistics.standard_deviation, f32::sqrt(2.0));
+        assert_eq!(statistics.wall_time_seconds, 2.0);
+        assert_eq!(
+            statistics.to_csv_row().split(',').count(),
+            CSV_HEADER.split(',').count()
+        );
+    }
+}
@@ -1,13 +1,31 @@
 //! Manages the UI
 
-use std::{ops::RangeInclusive, time::Instant};
+mod camera;
+mod comparison;
+mod display;
+mod editor;
+mod evolution;
+mod gait;
+mod gallery;
+mod leaderboard;
+mod setup;
+mod transport;
+mod turbo;
+
+pub(crate) use camera::{Camera, FollowMode, ScoreLine};
+
+use std::{mem, path::Path, slice, time::Instant};
+
+use uuid::Uuid;
 
 use crate::{
-    evolver::{Evolver, EvolverState},
-    simulation::{
-        Simulation, FLOOR_HEIGHT, FLOOR_TOP_Y, SCORE_PER_SCREEN, STEPS_PER_SECOND, WORLD_X_SIZE,
-        WORLD_Y_SIZE,
-    },
+    ancestry::{self, short_id},
+    creature::Creature,
+    evolver::{CreatureSnapshot, EvolverSettings, EvolverState, EvolverWorker, Snapshot},
+    hall_of_fame::HallOfFame,
+    replay::{Replay, ReplayPlayer},
+    simulation::{Fitness, SimulationView, Terrain, FLOOR_HEIGHT, FLOOR_TOP_Y, STEPS_PER_SECOND},
+    statistics::{GenerationStatistics, StatisticsFormat},
     util,
 };
 use eframe::{
@@ -16,24 +34,69 @@ use eframe::{
     Theme,
 };
 use egui::{
-    text::LayoutJob, Align, Color32, FontFamily, FontId, Image, Layout, Painter, Pos2, Rect,
-    RichText, Rounding, Stroke, TextFormat, Vec2,
+    plot::{Legend, Line, Plot, PlotPoints, Polygon},
+    text::LayoutJob,
+    Align, Color32, FontFamily, FontId, Image, Layout, Painter, Pos2, Rect, RichText, Rounding,
+    Stroke, TextFormat, Vec2,
 };
 
 use crate::res;
+use comparison::{Comparison, MAX_CONTESTANTS};
+use display::DisplayOptions;
+use editor::Editor;
+use gait::Gait;
+use gallery::Gallery;
+use setup::Setup;
+use transport::Transport;
 
-const SPEEDS: [f32; 11] = [0.0, 0.25, 0.5, 0.75, 1.0, 1.5, 2.0, 3.0, 5.0, 7.5, 10.0];
-const DEFAULT_SPEED: usize = 4;
 const MIN_MUSCLE_THICKNESS: f32 = 1.5;
 const MAX_MUSCLE_THICKNESS: f32 = 3.0;
-const DISTANCE_LINE_THICKNESS: f32 = 5.0;
+pub(crate) const DISTANCE_LINE_THICKNESS: f32 = 5.0;
+pub(crate) const SKY_COLOR: Color32 = Color32::from_rgb(122, 233, 255);
+pub(crate) const GROUND_COLOR: Color32 = Color32::from_rgb(75, 200, 75);
 const WHITE: Color32 = Color32::WHITE;
 const TEXT_COLOR: Color32 = WHITE;
-const CREATURE_SCORE_TEXT_SIZE: f32 = 20.0;
-const SCORE_LINE_TEXT_SIZE: f32 = 30.0;
+pub(crate) const CREATURE_SCORE_TEXT_SIZE: f32 = 20.0;
+pub(crate) const SCORE_LINE_TEXT_SIZE: f32 = 30.0;
+const FITNESS_CHART_SIZE: Vec2 = Vec2::new(400.0, 200.0);
+const BEST_LINE_COLOR: Color32 = Color32::from_rgb(40, 160, 40);
+const MEDIAN_LINE_COLOR: Color32 = Color32::from_rgb(40, 90, 200);
+const WORST_LINE_COLOR: Color32 = Color32::from_rgb(200, 60, 40);
+const SELECTION_TOLERANCE: f32 = 10.0; // How far from a node a click can be, in world units
+const DIMMED_OPACITY: f32 = 0.25;
+const GHOST_OPACITY: f32 = 0.4;
+const SELECTED_OUTLINE_THICKNESS: f32 = 2.0;
+const SELECTED_OUTLINE_COLOR: Color32 = Color32::from_rgb(255, 215, 0);
+const HIGHLIGHTED_OUTLINE_COLOR: Color32 = Color32::from_rgb(200, 40, 200);
+const DRAG_THRESHOLD: f32 = 6.0; // How far the pointer moves before a press is a drag, in screen pixels
+const CELL_WORLD_HEIGHT: f32 = 300.0; // How much of the world fits in a layout cell without any zoom
+const CELL_FLOOR_FRACTION: f32 = 0.8; // How far down a layout cell the top of the floor is
+const CELL_BORDER_THICKNESS: f32 = 1.0;
+const CELL_RANK_TEXT_SIZE: f32 = 20.0;
+const ZOOM_PER_SCROLL: f32 = 0.002; // Zoom factor is e^(scroll * this), so zooming in and out cancel out
 
 /// Initializes the UI
 pub fn init() {
+    run(None);
+}
+
+/// Initializes the UI and starts evolving straight away, with a first generation made from template creatures
+/// (see [Evolver::with_templates](crate::evolver::Evolver::with_templates))
+pub fn init_with_templates(templates: Vec<Creature>, random_immigrants: usize) {
+    let settings = EvolverSettings {
+        random_immigrants,
+        ..Default::default()
+    };
+
+    run(Some(setup::spawn_evolver(
+        settings,
+        templates,
+        HallOfFame::default_path(),
+    )));
+}
+
+/// Opens the window, starting on the simulation if there's an evolver to show or the main menu otherwise
+fn run(worker: Option<EvolverWorker>) {
     let native_options = eframe::NativeOptions {
         icon_data: Some(res::load_icon_data()),
         follow_system_theme: false,
@@ -46,7 +109,7 @@ pub fn init() {
     eframe::run_native(
         "Project Evolution",
         native_options,
-        Box::new(|cc| Box::new(App::new(cc))),
+        Box::new(|cc| Box::new(App::new(cc, worker))),
     );
 }
 
@@ -54,13 +117,35 @@ pub fn init() {
 
 /// Creates new egui ui struct used to populate objects into new Window
 struct App {
-    evolver: Evolver,
+    worker: Option<EvolverWorker>,
+    /// The latest [Snapshot] from the worker, which is drawn until the next one arrives
+    snapshot: Option<Snapshot>,
     state: AppState,
     last_frame: Option<Instant>,
-    speed_setting: usize,
-    screen_size: Vec2,
-    screen_offset_x: f32,
-    max_x: f32,
+    transport: Transport,
+    camera: Camera,
+    replay_player: Option<ReplayPlayer>,
+    replay_message: Option<String>,
+    show_percentile_band: bool,
+    selected_creature: Option<Uuid>,
+    editor: Editor,
+    menu_message: Option<String>,
+    ancestry_message: Option<String>,
+    setup: Setup,
+    display: DisplayOptions,
+    /// Where each creature with a cell of its own was painted, if they aren't overlapping
+    layout_cells: Vec<(Rect, Uuid)>,
+    gait: Gait,
+    gallery: Gallery,
+    comparison: Comparison,
+}
+
+/// Computes how thick to draw a muscle in world units, where stretched muscles are thinner
+pub(crate) fn muscle_thickness(current_length: f32, normal_length: f32) -> f32 {
+    let thickness_delta = (current_length / normal_length).clamp(0.5, 1.5);
+
+    MIN_MUSCLE_THICKNESS
+        + ((1.0 - (thickness_delta - 0.5)) * (MAX_MUSCLE_THICKNESS - MIN_MUSCLE_THICKNESS))
 }
 
 /// Utility method to paint text at a position
@@ -97,67 +182,90 @@ fn paint_text(
 
 impl App {
     /// Initializes the egui app
-    fn new(_cc: &eframe::CreationContext<'_>) -> Self {
+    fn new(cc: &eframe::CreationContext<'_>, worker: Option<EvolverWorker>) -> Self {
         // Customize egui here with cc.egui_ctx.set_fonts and cc.egui_ctx.set_visuals.
-        // Restore app state using cc.storage (requires the "persistence" feature).
         // Use the cc.gl (a glow::Context) to create graphics shaders and buffers that you can use
         // for e.g. egui::PaintCallback.
-        App {
-            speed_setting: DEFAULT_SPEED,
+        let mut app = App {
+            setup: Setup::load(cc.storage),
             ..Default::default()
+        };
+
+        if let Some(worker) = worker {
+            app.start_run(worker);
         }
+
+        app
+    }
+
+    /// Switches to showing a newly started run, which runs at the transport's speed
+    fn start_run(&mut self, worker: EvolverWorker) {
+        worker.set_speed(self.transport.speed());
+        worker.set_paused(self.transport.is_paused());
+        worker.set_turbo(self.transport.is_turbo());
+
+        self.worker = Some(worker);
+        self.snapshot = None;
+        self.state = AppState::Simulation;
+        self.last_frame = None;
+        self.selected_creature = None;
+        self.gait.restart();
+        self.layout_cells.clear();
+        self.ancestry_message = None;
+    }
+
+    /// Paints a [Simulation], or anything else that can be viewed like one, using the provided [Painter]
+    fn paint_simulation(
+        &self,
+        simulation: &impl SimulationView,
+        emphasis: Emphasis,
+        painter: &Painter,
+    ) {
+        self.paint_faded_simulation(simulation, emphasis, 1.0, painter);
     }
 
-    /// Paints a [Simulation] using the provided [Painter]
-    fn paint_simulation(&self, simulation: &Simulation, painter: &Painter) {
+    /// Paints a [Simulation] like [App::paint_simulation], but fainter by an opacity between 0 and 1
+    fn paint_faded_simulation(
+        &self,
+        simulation: &impl SimulationView,
+        emphasis: Emphasis,
+        fade: f32,
+        painter: &Painter,
+    ) {
         let creature = simulation.creature();
         let colors = creature.colors();
         let movement_parameters = creature.movement_parameters();
+        let opacity = fade
+            * match emphasis {
+                Emphasis::Dimmed => DIMMED_OPACITY,
+                Emphasis::Ghost => GHOST_OPACITY,
+                Emphasis::Normal | Emphasis::Selected | Emphasis::Highlighted => 1.0,
+            };
 
         // Paint muscles
         for (id, muscle) in creature.muscles() {
             let from_position = &simulation.get_position_of_node(muscle.from_id);
             let to_position = &simulation.get_position_of_node(muscle.to_id);
             let is_muscle_extending = simulation.is_muscle_extending(*id);
-            let mut from = util::transform_position_from_world_to_screen_pos2(
-                from_position,
-                &self.screen_size,
-            );
-            let mut to =
-                util::transform_position_from_world_to_screen_pos2(to_position, &self.screen_size);
-
-            from.x += self.screen_offset_x;
-            to.x += self.screen_offset_x;
+            let from = self.camera.to_screen(from_position);
+            let to = self.camera.to_screen(to_position);
 
             let muscle_movement_parameters = movement_parameters.get(id).unwrap();
             let normal_length = muscle_movement_parameters.muscle_length();
             let current_length = util::distance(from_position, to_position);
 
-            let mut thickness_delta = current_length / normal_length;
-
-            if thickness_delta < 0.5 {
-                thickness_delta = 0.5;
-            }
-
-            if thickness_delta > 1.5 {
-                thickness_delta = 1.5;
-            }
-
-            let thickness = MIN_MUSCLE_THICKNESS
-                + ((1.0 - (thickness_delta - 0.5)) * (MAX_MUSCLE_THICKNESS - MIN_MUSCLE_THICKNESS));
+            let thickness = muscle_thickness(current_length, normal_length);
 
             let muscle_color = if is_muscle_extending {
                 colors.muscle_extended()
             } else {
                 colors.muscle_contracted()
-            };
+            }
+            .linear_multiply(opacity);
 
             let line = egui::Shape::line(
                 vec![from, to],
-                Stroke::from((
-                    util::transform_x_from_world_to_screen(thickness, &self.screen_size),
-                    muscle_color,
-                )),
+                Stroke::from((self.camera.length_to_screen(thickness), muscle_color)),
             );
 
             painter.add(line);
@@ -166,16 +274,23 @@ impl App {
         // Paint nodes
         for (id, node) in creature.nodes() {
             let position = simulation.get_position_of_node(*id);
-            let mut pos2 =
-                util::transform_position_from_world_to_screen_pos2(&position, &self.screen_size);
+            let pos2 = self.camera.to_screen(&position);
 
-            pos2.x += self.screen_offset_x;
+            let stroke = match emphasis {
+                Emphasis::Selected => {
+                    Stroke::new(SELECTED_OUTLINE_THICKNESS, SELECTED_OUTLINE_COLOR)
+                }
+                Emphasis::Highlighted => {
+                    Stroke::new(SELECTED_OUTLINE_THICKNESS, HIGHLIGHTED_OUTLINE_COLOR)
+                }
+                Emphasis::Normal | Emphasis::Dimmed | Emphasis::Ghost => Stroke::none(),
+            };
 
             let circle = CircleShape {
                 center: pos2,
-                radius: util::transform_x_from_world_to_screen(node.size / 2.0, &self.screen_size),
-                fill: colors.node(),
-                stroke: Stroke::none(),
+                radius: self.camera.length_to_screen(node.size / 2.0),
+                fill: colors.node().linear_multiply(opacity),
+                stroke,
             };
 
             painter.add(circle);
@@ -185,104 +300,124 @@ impl App {
         {
             let score = simulation.get_score();
             let position = simulation.get_text_position();
-            let mut pos2 =
-                util::transform_position_from_world_to_screen_pos2(&position, &self.screen_size);
+            let mut pos2 = self.camera.to_screen(&position);
 
-            pos2.x += self.screen_offset_x;
             pos2.y -= CREATURE_SCORE_TEXT_SIZE;
 
             paint_text(
                 format!("{:.2}m", score),
                 pos2,
                 CREATURE_SCORE_TEXT_SIZE,
-                colors.score_text(),
+                colors.score_text().linear_multiply(opacity),
                 true,
                 painter,
             );
         }
     }
 
-    /// Paints a generation using the provided [Painter]
-    fn paint_generation(&self, generation: &Vec<Simulation>, painter: &Painter) {
-        for simulation in generation {
-            self.paint_simulation(simulation, painter);
+    /// Paints a generation with an emphasis for each creature, leaving out those without one, using the
+    /// provided [Painter]. Fainter creatures are painted underneath. If a creature is selected, it's painted on
+    /// top and highlighted while the rest are dimmed
+    fn paint_generation(
+        &self,
+        generation: &[impl SimulationView],
+        emphases: &[Option<Emphasis>],
+        painter: &Painter,
+    ) {
+        let selected = generation
+            .iter()
+            .find(|simulation| Some(*simulation.creature().id()) == self.selected_creature);
+
+        for layer in [Emphasis::Ghost, Emphasis::Normal, Emphasis::Highlighted] {
+            for (simulation, emphasis) in generation.iter().zip(emphases) {
+                if *emphasis != Some(layer)
+                    || Some(*simulation.creature().id()) == self.selected_creature
+                {
+                    continue;
+                }
+
+                let emphasis = match selected {
+                    Some(_) => Emphasis::Dimmed,
+                    None => layer,
+                };
+
+                self.paint_simulation(simulation, emphasis, painter);
+            }
+        }
+
+        if let Some(simulation) = selected {
+            self.paint_simulation(simulation, Emphasis::Selected, painter);
         }
     }
 
-    /// Paints the scenery using the provided [Painter]
-    fn paint_scenery(&self, painter: &Painter) {
+    /// Paints the scenery, including the terrain, using the provided [Painter]
+    fn paint_scenery(&self, terrain: Terrain, painter: &Painter) {
+        let screen_rect = self.camera.screen_rect();
+        let floor_top = self.camera.y_to_screen(FLOOR_TOP_Y);
+
         // Add sky
         let sky = RectShape {
-            rect: Rect {
-                min: Pos2::new(0.0, 0.0),
-                max: Pos2::new(
-                    util::transform_x_from_world_to_screen(WORLD_X_SIZE, &self.screen_size),
-                    util::transform_y_from_world_to_screen(WORLD_Y_SIZE, &self.screen_size),
-                ),
-            },
+            rect: screen_rect,
             rounding: Rounding::none(),
-            fill: Color32::from_rgb(122, 233, 255),
+            fill: SKY_COLOR,
             stroke: Stroke::none(),
         };
 
         painter.add(sky);
 
-        // Add ground
+        // Add ground, which goes all the way down past the bottom of the world
         let ground = RectShape {
             rect: Rect {
-                min: Pos2::new(
-                    0.0,
-                    util::transform_y_from_world_to_screen(FLOOR_TOP_Y, &self.screen_size),
-                ),
-                max: Pos2::new(
-                    util::transform_x_from_world_to_screen(WORLD_X_SIZE, &self.screen_size),
-                    util::transform_y_from_world_to_screen(WORLD_Y_SIZE, &self.screen_size),
-                ),
+                min: Pos2::new(screen_rect.min.x, floor_top.max(screen_rect.min.y)),
+                max: screen_rect.max,
             },
             rounding: Rounding::none(),
-            fill: Color32::from_rgb(75, 200, 75),
+            fill: GROUND_COLOR,
             stroke: Stroke::none(),
         };
 
         painter.add(ground);
 
-        // Add score lines
-        let middle_score = Simulation::x_to_score(f32::floor(self.max_x)) as i32;
-        // Intentionally extend range extra to cover edges
-        let score_range = RangeInclusive::new(
-            middle_score - (SCORE_PER_SCREEN),
-            middle_score + (SCORE_PER_SCREEN),
-        );
-
-        for score in score_range {
-            let mut height_scale = 0.25;
-            let minor = score % (SCORE_PER_SCREEN / 2) == 0;
-            let major = score % SCORE_PER_SCREEN == 0;
-
-            if major {
-                height_scale = 2.0 / 3.0;
-            } else if minor {
-                height_scale = 1.0 / 3.0;
-            }
+        // Add terrain, leaving out what's off screen
+        let (visible_top_left, visible_bottom_right) = self.camera.visible_bounds();
+
+        for (top_left, bottom_right) in
+            terrain.obstacles_between(visible_top_left.x, visible_bottom_right.x)
+        {
+            let obstacle = RectShape {
+                rect: Rect {
+                    min: self.camera.to_screen(&top_left),
+                    max: self.camera.to_screen(&bottom_right),
+                },
+                rounding: Rounding::none(),
+                fill: GROUND_COLOR,
+                stroke: Stroke::none(),
+            };
+
+            painter.add(obstacle);
+        }
 
-            let x = Simulation::score_to_x(score as f32);
-            let y = FLOOR_TOP_Y + (FLOOR_HEIGHT * height_scale);
+        // Add score lines
+        for ScoreLine {
+            score,
+            x,
+            height_scale,
+            labeled,
+        } in self.camera.score_lines()
+        {
+            let y = self
+                .camera
+                .y_to_screen(FLOOR_TOP_Y + (FLOOR_HEIGHT * height_scale));
 
             let line = RectShape {
                 rect: Rect {
                     min: Pos2::new(
-                        util::transform_x_from_world_to_screen(
-                            x - (DISTANCE_LINE_THICKNESS / 2.0),
-                            &self.screen_size,
-                        ) + self.screen_offset_x,
-                        util::transform_y_from_world_to_screen(FLOOR_TOP_Y, &self.screen_size),
+                        self.camera.x_to_screen(x - (DISTANCE_LINE_THICKNESS / 2.0)),
+                        floor_top,
                     ),
                     max: Pos2::new(
-                        util::transform_x_from_world_to_screen(
-                            x + (DISTANCE_LINE_THICKNESS / 2.0),
-                            &self.screen_size,
-                        ) + self.screen_offset_x,
-                        util::transform_y_from_world_to_screen(y, &self.screen_size),
+                        self.camera.x_to_screen(x + (DISTANCE_LINE_THICKNESS / 2.0)),
+                        y,
                     ),
                 },
                 rounding: Rounding::none(),
@@ -292,12 +427,8 @@ impl App {
 
             painter.add(line);
 
-            if minor || major {
-                let pos = Pos2::new(
-                    util::transform_x_from_world_to_screen(x, &self.screen_size)
-                        + self.screen_offset_x,
-                    util::transform_y_from_world_to_screen(y, &self.screen_size),
-                );
+            if labeled {
+                let pos = Pos2::new(self.camera.x_to_screen(x), y);
 
                 paint_text(
                     format!("{:.2}m", score),
@@ -312,16 +443,14 @@ impl App {
     }
 
     /// Paints the info text like the timer and generation
-    fn paint_info_text(&self, painter: &Painter) {
-        let state = self.evolver.state();
-        let mut position = Pos2::new(
-            util::transform_x_from_world_to_screen(WORLD_X_SIZE / 2.0, &self.screen_size),
-            0.0,
-        );
+    fn paint_info_text(&self, snapshot: &Snapshot, painter: &Painter) {
+        let state = snapshot.state();
+        let screen_rect = self.camera.screen_rect();
+        let mut position = Pos2::new(screen_rect.center().x, screen_rect.min.y);
 
         match state {
             EvolverState::SimulatingGeneration { steps_left } => {
-                let on_generation = self.evolver.on_generation();
+                let on_generation = snapshot.on_generation();
 
                 paint_text(
                     format!("Generation {}", on_generation),
@@ -341,95 +470,800 @@ impl App {
                     painter,
                 );
             }
-            EvolverState::Evolving { steps_left: _ } => {
-                paint_text(
-                    "Evolving...".to_owned(),
-                    position,
-                    40.0,
-                    TEXT_COLOR,
-                    true,
-                    painter,
-                );
-                position.y += 40.0;
-                paint_text(
-                    "The best creature's offspring are being generated.".to_owned(),
-                    position,
-                    30.0,
-                    TEXT_COLOR,
-                    true,
-                    painter,
-                );
+            EvolverState::Evolving { steps_left } => {
+                self.paint_evolution_text(snapshot, steps_left, position, painter);
             }
         };
     }
 
-    /// Paints the scene
-    fn paint_scene(&mut self, painter: &Painter) {
-        let generation = self.evolver.current_generation();
-        self.max_x = generation
+    /// Paints the scene as of a [Snapshot]
+    fn paint_scene(&mut self, snapshot: &Snapshot, painter: &Painter) {
+        let generation = snapshot.current_generation();
+        let emphases = self.display.snapshot_emphases(snapshot, &generation);
+        let selected = generation
+            .iter()
+            .find(|simulation| Some(*simulation.creature().id()) == self.selected_creature);
+        // The camera only follows the creatures being shown
+        let shown: Vec<_> = generation
             .iter()
-            .map(|simulation| simulation.get_bounds().1.x)
-            .max_by(util::cmp_f32)
-            .unwrap();
-        self.screen_offset_x = util::transform_x_from_world_to_screen(
-            (WORLD_X_SIZE * (2.0 / 3.0)) - self.max_x,
-            &self.screen_size,
-        );
-        self.paint_scenery(painter);
-        self.paint_generation(generation, painter);
-        self.paint_info_text(painter);
+            .zip(&emphases)
+            .filter(|(_, emphasis)| emphasis.is_some())
+            .map(|(view, _)| *view)
+            .collect();
+
+        self.camera.follow(&shown, selected);
+
+        let count = shown.len().min(self.display.cells());
+        let cells = self
+            .display
+            .layout()
+            .cells(self.camera.screen_rect(), count);
+
+        match (snapshot.state(), cells) {
+            // Everyone is shown together while evolving, to see who is selected
+            (EvolverState::Evolving { steps_left }, _) => {
+                self.layout_cells.clear();
+                self.paint_scenery(snapshot.settings().environment.terrain, painter);
+                self.paint_evolution(snapshot, &generation, steps_left, painter);
+            }
+            (EvolverState::SimulatingGeneration { .. }, Some(cells)) => {
+                self.paint_cells(snapshot, &shown, &cells, painter)
+            }
+            (EvolverState::SimulatingGeneration { .. }, None) => {
+                self.layout_cells.clear();
+                self.paint_scenery(snapshot.settings().environment.terrain, painter);
+                self.paint_generation(&generation, &emphases, painter);
+
+                if let Some(selected) = selected {
+                    self.paint_trails(snapshot, selected, painter);
+                }
+            }
+        }
+
+        self.paint_info_text(snapshot, painter);
+    }
+
+    /// Paints the furthest of the shown creatures in cells of their own, each with its own ground and
+    /// camera following it. The app's camera still sets the zoom and how the creatures are followed
+    fn paint_cells(
+        &mut self,
+        snapshot: &Snapshot,
+        shown: &[CreatureSnapshot],
+        cells: &[Rect],
+        painter: &Painter,
+    ) {
+        let mut by_score = shown.to_vec();
+        by_score.sort_by(|a, b| util::cmp_f32(&b.get_score(), &a.get_score()));
+
+        self.layout_cells.clear();
+
+        for (rank, (view, cell)) in by_score.iter().zip(cells).enumerate() {
+            let cell_painter = painter.with_clip_rect(*cell);
+            let emphasis = if Some(*view.creature().id()) == self.selected_creature {
+                Emphasis::Selected
+            } else {
+                Emphasis::Normal
+            };
+            let mut camera = self.camera.clone();
+
+            camera.set_screen_rect(*cell);
+            camera.fit_height(CELL_WORLD_HEIGHT / self.camera.zoom());
+            camera.follow(slice::from_ref(view), Some(view));
+            camera.rest_on_floor(CELL_FLOOR_FRACTION);
+
+            // Painting uses the app's camera, so the cell's is swapped in
+            mem::swap(&mut self.camera, &mut camera);
+            self.paint_scenery(snapshot.settings().environment.terrain, &cell_painter);
+
+            if emphasis == Emphasis::Selected {
+                self.paint_trails(snapshot, view, &cell_painter);
+            }
+
+            self.paint_simulation(view, emphasis, &cell_painter);
+            mem::swap(&mut self.camera, &mut camera);
+
+            paint_text(
+                format!("#{}", rank + 1),
+                cell.min + Vec2::new(5.0, 2.0),
+                CELL_RANK_TEXT_SIZE,
+                WHITE,
+                false,
+                &cell_painter,
+            );
+            cell_painter.rect_stroke(
+                *cell,
+                Rounding::none(),
+                Stroke::new(CELL_BORDER_THICKNESS, WHITE),
+            );
+
+            self.layout_cells.push((*cell, *view.creature().id()));
+        }
+    }
+
+    /// Zooms the camera with the scroll wheel and pans it by dragging, ignoring the controls and any windows.
+    /// Panning stops the camera from following anything
+    fn control_camera(&mut self, ui: &egui::Ui, controls: Rect) {
+        // Copied out so the input isn't locked while checking layers below
+        let (scroll, hover_position, press_origin, primary_down, delta) = {
+            let input = ui.input();
+
+            (
+                input.scroll_delta.y,
+                input.pointer.hover_pos(),
+                input.pointer.press_origin(),
+                input.pointer.primary_down(),
+                input.pointer.delta(),
+            )
+        };
+        let in_scene = |position: Pos2| {
+            ui.max_rect().contains(position)
+                && !controls.contains(position)
+                && ui.ctx().layer_id_at(position) == Some(ui.layer_id())
+        };
+
+        if let Some(position) = hover_position.filter(|position| in_scene(*position)) {
+            if scroll != 0.0 {
+                self.camera
+                    .zoom_around(position, (scroll * ZOOM_PER_SCROLL).exp());
+            }
+        }
+
+        // Only counted as a drag once it's moved too far to be a click, so clicking still selects
+        let dragging = match (press_origin, hover_position) {
+            (Some(origin), Some(position)) => {
+                primary_down && in_scene(origin) && origin.distance(position) > DRAG_THRESHOLD
+            }
+            _ => false,
+        };
+
+        if dragging {
+            self.camera.set_follow_mode(FollowMode::Fixed);
+            self.camera.pan(delta);
+        }
+    }
+
+    /// Renders the camera's follow mode options
+    fn render_camera_controls(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal_top(|ui| {
+            ui.label(RichText::new("Camera:").color(TEXT_COLOR));
+
+            let mut follow_mode = self.camera.follow_mode();
+
+            for option in FollowMode::ALL {
+                ui.selectable_value(&mut follow_mode, option, option.name());
+            }
+
+            self.camera.set_follow_mode(follow_mode);
+
+            if ui.button("Reset zoom").clicked() {
+                self.camera
+                    .zoom_around(self.camera.screen_rect().center(), 1.0 / self.camera.zoom());
+            }
+        });
     }
 
     /// Renders the simulation
     fn render_simulation(&mut self, ui: &mut egui::Ui) {
+        // Shared with the worker, so this is cheap and leaves the app free to change
+        let snapshot = match self.snapshot.clone() {
+            Some(snapshot) => snapshot,
+            None => {
+                self.paint_scenery(Terrain::Flat, ui.painter());
+                ui.label(RichText::new("Starting...").color(TEXT_COLOR));
+                return;
+            }
+        };
+
+        if self.transport.is_turbo() {
+            self.render_turbo_dashboard(ui, &snapshot);
+            return;
+        }
+
+        self.follow_selection_to_offspring(&snapshot);
+        self.paint_scene(&snapshot, ui.painter());
+
+        let controls = egui::containers::Frame::none()
+            .inner_margin(10.0)
+            .show(ui, |ui| {
+                self.render_transport_bar(ui);
+
+                ui.add_space(10.0);
+
+                ui.horizontal_top(|ui| {
+                    ui.label(RichText::new("Statistics:").color(TEXT_COLOR));
+
+                    for format in [StatisticsFormat::Csv, StatisticsFormat::JsonLines] {
+                        let text = match format {
+                            StatisticsFormat::Csv => "Write CSV",
+                            StatisticsFormat::JsonLines => "Write JSON Lines",
+                        };
+
+                        if ui.button(text).clicked() {
+                            if let Some(worker) = &self.worker {
+                                worker.export_statistics_to(format.default_path());
+                            }
+                        }
+                    }
+
+                    let message = match snapshot.statistics_export() {
+                        Some(Ok(path)) => Some(format!("Writing to {}", path.display())),
+                        Some(Err(error)) => Some(format!("Failed to write statistics: {}", error)),
+                        None => None,
+                    };
+
+                    if let Some(message) = message {
+                        ui.label(RichText::new(message).color(TEXT_COLOR));
+                    }
+                });
+
+                ui.horizontal_top(|ui| {
+                    let living_creatures = Self::living_creatures(&snapshot);
+                    let (surviving, founders) =
+                        snapshot.ancestry().surviving_founders(&living_creatures);
+
+                    ui.label(
+                        RichText::new(format!(
+                            "Ancestry: {} of {} founding lineages survive",
+                            surviving, founders
+                        ))
+                        .color(TEXT_COLOR),
+                    );
+
+                    if ui.button("Write run family tree").clicked() {
+                        let dot = snapshot.ancestry().run_to_dot(&living_creatures);
+
+                        self.ancestry_message =
+                            Some(Self::save_dot(&dot, &ancestry::default_dot_path("run")));
+                    }
+
+                    if let Some(message) = &self.ancestry_message {
+                        ui.label(RichText::new(message).color(TEXT_COLOR));
+                    }
+                });
+
+                if let Some(error) = snapshot.hall_of_fame_error() {
+                    ui.label(
+                        RichText::new(format!("Failed to save the hall of fame: {}", error))
+                            .color(TEXT_COLOR),
+                    );
+                }
+
+                self.render_camera_controls(ui);
+
+                egui::CollapsingHeader::new(RichText::new("Fitness history").color(TEXT_COLOR))
+                    .show(ui, |ui| {
+                        ui.checkbox(
+                            &mut self.show_percentile_band,
+                            RichText::new("Show 10th to 90th percentile").color(TEXT_COLOR),
+                        );
+
+                        Self::render_fitness_chart(
+                            ui,
+                            &snapshot.generation_statistics(),
+                            self.show_percentile_band,
+                            FITNESS_CHART_SIZE,
+                        );
+                    });
+
+                if let Some(replay) = snapshot.best_replay() {
+                    if ui.button("Replay last generation's best").clicked() {
+                        self.replay_player = Some(ReplayPlayer::new(replay.clone()));
+                        self.replay_message = None;
+                        self.state = AppState::Replay;
+                        self.last_frame = None;
+                    }
+                }
+
+                let compare_text = format!(
+                    "Compare creatures ({}/{})",
+                    self.comparison.contestant_count(),
+                    MAX_CONTESTANTS
+                );
+
+                if ui.button(compare_text).clicked() {
+                    self.open_comparison();
+                }
+
+                if ui.button("Return to menu").clicked() {
+                    self.end_run();
+                }
+            });
+
+        self.control_camera(ui, controls.response.rect);
+        self.select_clicked_creature(ui, &snapshot, controls.response.rect);
+        self.render_inspector(ui.ctx(), &snapshot);
+    }
+
+    /// Keeps the newest [Snapshot] the worker has published, if there's a run
+    fn receive_snapshot(&mut self) {
+        if let Some(worker) = &self.worker {
+            self.gait.track(worker, self.selected_creature);
+        }
+
+        if let Some(snapshot) = self
+            .worker
+            .as_ref()
+            .and_then(EvolverWorker::latest_snapshot)
+        {
+            self.snapshot = Some(snapshot);
+        }
+    }
+
+    /// Stops the run and goes back to the main menu
+    fn end_run(&mut self) {
+        self.state = AppState::MainMenu;
+        self.worker = None;
+        self.snapshot = None;
+        self.last_frame = None;
+        self.ancestry_message = None;
+        self.selected_creature = None;
+    }
+
+    /// Selects the creature closest to where the scene was clicked, or clears the selection if nothing
+    /// was close enough. Clicks on the controls or any window are ignored
+    fn select_clicked_creature(&mut self, ui: &egui::Ui, snapshot: &Snapshot, controls: Rect) {
+        // Copied out so the input isn't locked while checking layers below
+        let (clicked, position) = {
+            let pointer = &ui.input().pointer;
+
+            (pointer.primary_clicked(), pointer.interact_pos())
+        };
+
+        let position = match position {
+            Some(position) if clicked => position,
+            _ => return,
+        };
+
+        // Side panels share the scene's layer, so they're ruled out by where the scene is
+        if !ui.max_rect().contains(position)
+            || controls.contains(position)
+            || ui.ctx().layer_id_at(position) != Some(ui.layer_id())
+        {
+            return;
+        }
+
+        // With a cell each, clicking a creature's cell selects it
+        if !self.layout_cells.is_empty() {
+            self.selected_creature = self
+                .layout_cells
+                .iter()
+                .find(|(cell, _)| cell.contains(position))
+                .map(|(_, id)| *id);
+
+            return;
+        }
+
+        let world_position = self.camera.to_world(position);
+
+        self.selected_creature = snapshot
+            .current_generation()
+            .iter()
+            .map(|simulation| {
+                (
+                    simulation.get_distance_to_closest_node(&world_position),
+                    simulation,
+                )
+            })
+            .filter(|(distance, _)| *distance <= SELECTION_TOLERANCE)
+            .min_by(|(a, _), (b, _)| util::cmp_f32(a, b))
+            .map(|(_, simulation)| *simulation.creature().id());
+    }
+
+    /// Moves the selection to one of the selected creature's offspring once its generation is replaced,
+    /// clearing it if there are none
+    fn follow_selection_to_offspring(&mut self, snapshot: &Snapshot) {
+        let selected = match self.selected_creature {
+            Some(selected) => selected,
+            None => return,
+        };
+
+        let generation = snapshot.current_generation();
+
+        if generation
+            .iter()
+            .any(|simulation| *simulation.creature().id() == selected)
+        {
+            return;
+        }
+
+        self.selected_creature = generation
+            .iter()
+            .map(|simulation| simulation.creature())
+            .find(|creature| creature.parent_id() == Some(&selected))
+            .map(|creature| *creature.id());
+    }
+
+    /// Renders a window describing the selected creature, if any
+    fn render_inspector(&mut self, ctx: &egui::Context, snapshot: &Snapshot) {
+        let selected = match self.selected_creature {
+            Some(selected) => selected,
+            None => return,
+        };
+
+        let fitness = snapshot.settings().fitness;
+        let generation = snapshot.current_generation();
+        let simulation = match generation
+            .iter()
+            .find(|simulation| *simulation.creature().id() == selected)
+        {
+            Some(simulation) => simulation,
+            None => return,
+        };
+
+        let creature = simulation.creature();
+        let creature_fitness = simulation.get_fitness();
+        let rank = 1 + generation
+            .iter()
+            .filter(|other| other.get_fitness() > creature_fitness)
+            .count();
+        let siblings = generation
+            .iter()
+            .filter(|other| {
+                other.creature().parent_id().is_some()
+                    && other.creature().parent_id() == creature.parent_id()
+                    && other.creature().id() != creature.id()
+            })
+            .count();
+
+        let ancestry = snapshot.ancestry();
+        let mut open = true;
+        let mut export_family_tree = false;
+        let mut add_to_comparison = false;
+        let can_add_to_comparison = self.comparison.contestant_count() < MAX_CONTESTANTS;
+
+        egui::Window::new("Inspector")
+            .open(&mut open)
+            .resizable(false)
+            .show(ctx, |ui| {
+                egui::Grid::new("Inspector summary")
+                    .num_columns(2)
+                    .show(ui, |ui| {
+                        ui.label("Id");
+                        ui.label(creature.id().to_string());
+                        ui.end_row();
+
+                        ui.label("Score");
+                        ui.label(format!("{:.2}m", simulation.get_score()));
+                        ui.end_row();
+
+                        if fitness != Fitness::Distance {
+                            ui.label(fitness.name());
+                            ui.label(format!("{:.2}", creature_fitness));
+                            ui.end_row();
+                        }
+
+                        ui.label("Rank");
+                        ui.label(format!("{} of {}", rank, generation.len()));
+                        ui.end_row();
+
+                        ui.label("Nodes");
+                        ui.label(creature.nodes().len().to_string());
+                        ui.end_row();
+
+                        ui.label("Muscles");
+                        ui.label(creature.muscles().len().to_string());
+                        ui.end_row();
+
+                        ui.label("Controller");
+                        ui.label(creature.controller().name());
+                        ui.end_row();
+
+                        ui.label("Born in generation");
+                        ui.label(creature.lineage().birth_generation().to_string());
+                        ui.end_row();
+
+                        ui.label("Parent");
+                        ui.label(match creature.parent_id() {
+                            Some(parent_id) => parent_id.to_string(),
+                            None => "None (randomly generated)".to_string(),
+                        });
+                        ui.end_row();
+
+                        ui.label("Mutations");
+                        ui.label(
+                            creature
+                                .lineage()
+                                .mutations()
+                                .iter()
+                                .map(|mutation| mutation.name())
+                                .collect::<Vec<_>>()
+                                .join(", "),
+                        );
+                        ui.end_row();
+
+                        ui.label("Ancestors");
+                        ui.label(ancestry.ancestors(creature.id()).len().to_string());
+                        ui.end_row();
+
+                        ui.label("Founder");
+                        ui.label(short_id(&ancestry.founder(creature.id())));
+                        ui.end_row();
+
+                        ui.label("Siblings");
+                        ui.label(siblings.to_string());
+                        ui.end_row();
+                    });
+
+                ui.horizontal(|ui| {
+                    export_family_tree = ui.button("Write family tree").clicked();
+
+                    let compare_text = format!(
+                        "Add to comparison ({}/{})",
+                        self.comparison.contestant_count(),
+                        MAX_CONTESTANTS
+                    );
+
+                    add_to_comparison = ui
+                        .add_enabled(can_add_to_comparison, egui::Button::new(compare_text))
+                        .clicked();
+                });
+
+                egui::CollapsingHeader::new("Muscles").show(ui, |ui| {
+                    // Sorted so the rows don't move around between frames
+                    let mut muscles: Vec<_> = creature.movement_parameters().iter().collect();
+                    muscles.sort_by_key(|(id, _)| **id);
+
+                    egui::Grid::new("Inspector muscles")
+                        .num_columns(6)
+                        .striped(true)
+                        .show(ui, |ui| {
+                            for header in [
+                                "Muscle",
+                                "Length",
+                                "Extension",
+                                "Contraction",
+                                "Phase",
+                                "Waveform",
+                            ] {
+                                ui.strong(header);
+                            }
+                            ui.end_row();
+
+                            let to_seconds = |steps: i32| steps as f32 / STEPS_PER_SECOND as f32;
+
+                            for (index, (_, movement_parameters)) in muscles.iter().enumerate() {
+                                ui.label((index + 1).to_string());
+                                ui.label(format!("{:.1}", movement_parameters.muscle_length()));
+                                ui.label(format!(
+                                    "{:.2}s",
+                                    to_seconds(movement_parameters.extension_period())
+                                ));
+                                ui.label(format!(
+                                    "{:.2}s",
+                                    to_seconds(movement_parameters.contraction_period())
+                                ));
+                                ui.label(format!(
+                                    "{:.2}s",
+                                    to_seconds(movement_parameters.phase_offset())
+                                ));
+                                ui.label(movement_parameters.waveform().name());
+                                ui.end_row();
+                            }
+                        });
+                });
+            });
+
+        if add_to_comparison {
+            let label = format!("Generation {} #{}", snapshot.on_generation(), rank);
+
+            // Can't fail, since the button is only enabled when there's room
+            let _ = self.comparison.add(label, creature);
+        }
+
+        if export_family_tree {
+            let dot = ancestry.family_tree_to_dot(&selected, &Self::living_creatures(snapshot));
+
+            self.ancestry_message = Some(Self::save_dot(
+                &dot,
+                &ancestry::default_dot_path(&short_id(&selected)),
+            ));
+        }
+
+        if !open {
+            self.selected_creature = None;
+        }
+    }
+
+    /// Gets the ids of the creatures in a snapshot's current generation
+    fn living_creatures(snapshot: &Snapshot) -> Vec<Uuid> {
+        snapshot
+            .current_generation()
+            .iter()
+            .map(|simulation| *simulation.creature().id())
+            .collect()
+    }
+
+    /// Saves a DOT graph, returning a message describing where it was saved or what went wrong
+    fn save_dot(dot: &str, path: &Path) -> String {
+        match ancestry::save_dot(dot, path) {
+            Ok(_) => format!("Wrote {}", path.display()),
+            Err(error) => format!("Failed to write family tree: {}", error),
+        }
+    }
+
+    /// Renders a chart of the best, median and worst scores of each generation so far, optionally with
+    /// the band between the 10th and 90th percentiles
+    fn render_fitness_chart(
+        ui: &mut egui::Ui,
+        statistics: &[GenerationStatistics],
+        show_percentile_band: bool,
+        size: Vec2,
+    ) {
+        if statistics.is_empty() {
+            ui.label(RichText::new("Waiting for the first generation...").color(TEXT_COLOR));
+            return;
+        }
+
+        let points = |score: fn(&GenerationStatistics) -> f32| -> PlotPoints {
+            statistics
+                .iter()
+                .map(|statistics| [statistics.generation as f64, score(statistics) as f64])
+                .collect()
+        };
+
+        Plot::new("Fitness history")
+            .width(size.x)
+            .height(size.y)
+            .legend(Legend::default())
+            .allow_drag(false)
+            .allow_zoom(false)
+            .allow_scroll(false)
+            .allow_boxed_zoom(false)
+            .show(ui, |plot_ui| {
+                if show_percentile_band {
+                    // Polygons are filled as if convex, so the band is drawn one generation at a time
+                    for pair in statistics.windows(2) {
+                        let band = PlotPoints::new(vec![
+                            [pair[0].generation as f64, pair[0].p90 as f64],
+                            [pair[1].generation as f64, pair[1].p90 as f64],
+                            [pair[1].generation as f64, pair[1].p10 as f64],
+                            [pair[0].generation as f64, pair[0].p10 as f64],
+                        ]);
+
+                        plot_ui.polygon(
+                            Polygon::new(band)
+                                .color(MEDIAN_LINE_COLOR)
+                                .width(0.0)
+                                .fill_alpha(0.2)
+                                .name("10th to 90th percentile"),
+                        );
+                    }
+                }
+
+                plot_ui.line(
+                    Line::new(points(|statistics| statistics.best))
+                        .color(BEST_LINE_COLOR)
+                        .name("Best"),
+                );
+                plot_ui.line(
+                    Line::new(points(|statistics| statistics.median))
+                        .color(MEDIAN_LINE_COLOR)
+                        .name("Median"),
+                );
+                plot_ui.line(
+                    Line::new(points(|statistics| statistics.worst))
+                        .color(WORST_LINE_COLOR)
+                        .name("Worst"),
+                );
+            });
+    }
+
+    /// Renders the replay being played
+    fn render_replay(&mut self, ui: &mut egui::Ui) {
         let now = Instant::now();
 
+        let mut player = match self.replay_player.take() {
+            Some(player) => player,
+            None => {
+                self.state = AppState::Simulation;
+                return;
+            }
+        };
+
         if let Some(last_frame) = self.last_frame {
-            self.evolver.run(
-                now.duration_since(last_frame)
-                    .mul_f32(SPEEDS[self.speed_setting]),
-            );
+            player.run(now.duration_since(last_frame));
         }
 
-        self.paint_scene(ui.painter());
-
         self.last_frame = Some(now);
 
-        egui::containers::Frame::none()
+        // Paint the current frame
+        {
+            let frame = player.current_frame();
+            let painter = ui.painter();
+
+            self.camera.follow(slice::from_ref(&frame), Some(&frame));
+            self.paint_scenery(frame.terrain(), painter);
+            self.paint_simulation(&frame, Emphasis::Normal, painter);
+
+            let screen_rect = self.camera.screen_rect();
+            let position = Pos2::new(screen_rect.center().x, screen_rect.min.y);
+
+            paint_text(
+                format!(
+                    "Replay: {:.2}s",
+                    player.frame_index() as f32 * player.replay().frame_duration().as_secs_f32()
+                ),
+                position,
+                40.0,
+                TEXT_COLOR,
+                true,
+                painter,
+            );
+        }
+
+        let controls = egui::containers::Frame::none()
             .inner_margin(10.0)
             .show(ui, |ui| {
                 ui.horizontal_top(|ui| {
                     if ui.button("<").clicked() {
-                        self.speed_setting = usize::max(0, self.speed_setting - 1);
+                        player.set_playing(false);
+                        player.step_backward();
                     }
-                    ui.label(
-                    RichText::new(
-                            format! {"Speed: {}x", SPEEDS.get(self.speed_setting as usize).unwrap()},
-                        )
-                        .font(FontId::proportional(25.0))
-                        .color(TEXT_COLOR),
-                    );
+
+                    let play_text = if player.is_playing() { "Pause" } else { "Play" };
+
+                    if ui.button(play_text).clicked() {
+                        player.set_playing(!player.is_playing());
+                    }
+
                     if ui.button(">").clicked() {
-                        self.speed_setting = usize::min(SPEEDS.len() - 1, self.speed_setting + 1);
+                        player.set_playing(false);
+                        player.step_forward();
+                    }
+
+                    let mut frame = player.frame_index();
+                    let last_frame = player.replay().frame_count().saturating_sub(1);
+
+                    if ui
+                        .add(egui::Slider::new(&mut frame, 0..=last_frame).text("Frame"))
+                        .changed()
+                    {
+                        player.set_playing(false);
+                        player.set_frame(frame);
                     }
                 });
 
                 ui.add_space(10.0);
 
-                if ui.button("Return to menu").clicked() {
-                    self.state = AppState::MainMenu;
-                    self.evolver = Evolver::new();
+                if ui.button("Save").clicked() {
+                    self.replay_message = Some(Self::save_replay(player.replay()));
+                }
+
+                if let Some(message) = &self.replay_message {
+                    ui.label(RichText::new(message).color(TEXT_COLOR));
+                }
+
+                if ui.button("Return to simulation").clicked() {
+                    self.state = AppState::Simulation;
                     self.last_frame = None;
                 }
             });
+
+        self.control_camera(ui, controls.response.rect);
+        self.replay_player = Some(player);
+    }
+
+    /// Saves a replay and its creature, returning a message describing where they were saved or what went wrong
+    fn save_replay(replay: &Replay) -> String {
+        let creature_path = replay.creature().default_path();
+        let replay_path = Replay::path_next_to(&creature_path);
+
+        let result = replay
+            .creature()
+            .save(&creature_path)
+            .and_then(|_| replay.save(&replay_path));
+
+        match result {
+            Ok(_) => format!("Saved to {}", replay_path.display()),
+            Err(error) => format!("Failed to save: {}", error),
+        }
     }
 
     /// Renders the main menu
     fn render_main_menu(&mut self, ui: &mut egui::Ui) {
         let painter = ui.painter();
-        self.max_x = 0.0;
-        self.screen_offset_x = 0.0;
-        self.paint_scenery(&painter);
+        self.camera.reset();
+        self.paint_scenery(Terrain::Flat, painter);
 
         ui.with_layout(Layout::top_down(Align::Center), |ui| {
             let texture_handle = ui.ctx().load_texture(
@@ -444,7 +1278,52 @@ impl App {
                 .button(RichText::new("Begin").font(FontId::proportional(40.0)))
                 .clicked()
             {
-                self.state = AppState::Simulation;
+                self.state = AppState::Setup;
+                self.menu_message = None;
+            }
+
+            if ui
+                .button(RichText::new("Creature editor").font(FontId::proportional(25.0)))
+                .clicked()
+            {
+                self.state = AppState::Editor;
+                self.last_frame = None;
+            }
+
+            if ui
+                .button(RichText::new("Hall of fame").font(FontId::proportional(25.0)))
+                .clicked()
+            {
+                self.gallery = Gallery::open();
+                self.state = AppState::Gallery;
+                self.last_frame = None;
+            }
+
+            if ui
+                .button(RichText::new("Compare creatures").font(FontId::proportional(25.0)))
+                .clicked()
+            {
+                self.open_comparison();
+            }
+
+            if ui
+                .button(RichText::new("Evolve saved creatures").font(FontId::proportional(25.0)))
+                .clicked()
+            {
+                // Uses the last run's settings, which can be changed in the run setup
+                match setup::load_saved_creatures()
+                    .and_then(|templates| self.setup.start_from(templates))
+                {
+                    Ok(worker) => {
+                        self.start_run(worker);
+                        self.menu_message = None;
+                    }
+                    Err(message) => self.menu_message = Some(message),
+                }
+            }
+
+            if let Some(message) = &self.menu_message {
+                ui.label(RichText::new(message).color(TEXT_COLOR));
             }
         });
     }
@@ -463,29 +1342,68 @@ impl eframe::App for App {
             ..Default::default()
         };
 
+        self.receive_snapshot();
+
+        if matches!(self.state, AppState::Simulation) && !self.transport.is_turbo() {
+            egui::SidePanel::left("Leaderboard").show(ctx, |ui| self.render_leaderboard(ui));
+            egui::SidePanel::right("Display options").show(ctx, |ui| {
+                self.render_display_options(ui);
+                ui.add_space(5.0);
+                self.render_gait_options(ui);
+            });
+
+            if self.gait.is_diagram_shown() {
+                egui::TopBottomPanel::bottom("Gait diagram")
+                    .show(ctx, |ui| self.render_gait_diagram(ui));
+            }
+        }
+
         egui::CentralPanel::default()
             .frame(central_frame)
             .show(ctx, |ui| {
-                self.screen_size = ui.available_size();
+                self.camera.set_screen_rect(ui.max_rect());
 
                 match self.state {
                     AppState::MainMenu => self.render_main_menu(ui),
                     AppState::Simulation => self.render_simulation(ui),
+                    AppState::Replay => self.render_replay(ui),
+                    AppState::Editor => self.render_editor(ui),
+                    AppState::Setup => self.render_setup(ui),
+                    AppState::Gallery => self.render_gallery(ui),
+                    AppState::Comparison => self.render_comparison(ui),
                 }
             });
 
         // Logic to continuously re-render the UI
         ctx.request_repaint();
     }
+
+    /// Called every so often and on shutdown to remember the run setup
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        self.setup.save(storage);
+    }
+}
+
+/// How strongly a creature is painted compared to the others
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Emphasis {
+    Normal,
+    Selected,
+    Dimmed,
+    /// Translucent, for creatures that aren't leading
+    Ghost,
+    /// Outlined, for creatures picked out by the [DisplayOptions]
+    Highlighted,
 }
 
+#[derive(Default)]
 enum AppState {
+    #[default]
     MainMenu,
     Simulation,
-}
-
-impl Default for AppState {
-    fn default() -> Self {
-        Self::MainMenu
-    }
+    Replay,
+    Editor,
+    Setup,
+    Gallery,
+    Comparison,
 }
@@ -0,0 +1,333 @@
+//! The camera, which maps the world onto the screen with a uniform scale and zooms, pans and follows creatures
+
+use std::ops::RangeInclusive;
+
+use egui::{Pos2, Rect, Vec2};
+use rapier::prelude::Vector;
+
+use crate::{
+    simulation::{
+        Simulation, SimulationView, FLOOR_TOP_Y, SCORE_PER_SCREEN, WORLD_X_SIZE, WORLD_Y_SIZE,
+    },
+    util,
+};
+
+const ZOOM_RANGE: RangeInclusive<f32> = 0.1..=10.0;
+const LEADER_SCREEN_FRACTION: f32 = 2.0 / 3.0; // How far across the screen the leader is kept
+const MIN_SCORE_LINE_SPACING: f32 = 4.0; // In screen pixels, below which lines are skipped
+const MIN_SCORE_LABEL_SPACING: f32 = 120.0; // In screen pixels, below which labels are skipped
+
+/// A line on the ground marking a score
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct ScoreLine {
+    pub(crate) score: i32,
+    /// The world x of the line
+    pub(crate) x: f32,
+    /// How far down the ground the line goes, as a fraction of the ground's height
+    pub(crate) height_scale: f32,
+    pub(crate) labeled: bool,
+}
+
+/// What the [Camera] keeps in view
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub(crate) enum FollowMode {
+    /// The creature that has gone the furthest, two thirds of the way across the screen
+    #[default]
+    Leader,
+    /// The selected creature, in the middle of the screen
+    Selected,
+    /// The average position of every creature
+    Centroid,
+    /// Stays where it's panned to
+    Fixed,
+}
+
+impl FollowMode {
+    /// Every follow mode, in the order they're shown
+    pub(crate) const ALL: [FollowMode; 4] = [
+        FollowMode::Leader,
+        FollowMode::Selected,
+        FollowMode::Centroid,
+        FollowMode::Fixed,
+    ];
+
+    /// The name of the follow mode
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            FollowMode::Leader => "Leader",
+            FollowMode::Selected => "Selected",
+            FollowMode::Centroid => "Centroid",
+            FollowMode::Fixed => "Fixed",
+        }
+    }
+}
+
+/// Converts between world and screen coordinates, scaling both axes equally so shapes keep their proportions.
+/// At a zoom of 1 the whole height or width of the world fits on the screen, whichever is smaller
+#[derive(Debug, Clone)]
+pub(crate) struct Camera {
+    screen_rect: Rect,
+    center: Vector<f32>,
+    zoom: f32,
+    follow_mode: FollowMode,
+}
+
+impl Camera {
+    /// Creates a camera looking at the middle of the world, drawing to a screen of a certain size
+    pub(crate) fn new(screen_size: Vec2) -> Camera {
+        Camera {
+            screen_rect: Rect::from_min_size(Pos2::ZERO, screen_size),
+            center: Vector::new(WORLD_X_SIZE / 2.0, WORLD_Y_SIZE / 2.0),
+            zoom: 1.0,
+            follow_mode: FollowMode::default(),
+        }
+    }
+
+    /// Gets the area of the screen the world is drawn to
+    pub(crate) fn screen_rect(&self) -> Rect {
+        self.screen_rect
+    }
+
+    /// Sets the area of the screen the world is drawn to
+    pub(crate) fn set_screen_rect(&mut self, screen_rect: Rect) {
+        self.screen_rect = screen_rect;
+    }
+
+    /// Gets what the camera keeps in view
+    pub(crate) fn follow_mode(&self) -> FollowMode {
+        self.follow_mode
+    }
+
+    /// Sets what the camera keeps in view
+    pub(crate) fn set_follow_mode(&mut self, follow_mode: FollowMode) {
+        self.follow_mode = follow_mode;
+    }
+
+    /// Gets the zoom, where 1 fits the world on the screen
+    pub(crate) fn zoom(&self) -> f32 {
+        self.zoom
+    }
+
+    /// Looks at the middle of the world without any zoom
+    pub(crate) fn reset(&mut self) {
+        self.center = Vector::new(WORLD_X_SIZE / 2.0, WORLD_Y_SIZE / 2.0);
+        self.zoom = 1.0;
+    }
+
+    /// Zooms so a certain height of the world fits on the screen
+    pub(crate) fn fit_height(&mut self, height: f32) {
+        let size = self.screen_rect.size();
+        let unzoomed_scale = f32::min(size.x / WORLD_X_SIZE, size.y / WORLD_Y_SIZE);
+
+        self.zoom = size.y / (height * unzoomed_scale);
+    }
+
+    /// Moves the camera up or down so the top of the floor is a fraction of the way down the screen
+    pub(crate) fn rest_on_floor(&mut self, fraction: f32) {
+        let y = FLOOR_TOP_Y - self.visible_size().y * (fraction - 0.5);
+
+        self.look_at(Vector::new(self.center.x, y));
+    }
+
+    /// Gets how many screen pixels one world unit takes up
+    pub(crate) fn scale(&self) -> f32 {
+        let size = self.screen_rect.size();
+
+        f32::min(size.x / WORLD_X_SIZE, size.y / WORLD_Y_SIZE) * self.zoom
+    }
+
+    /// Gets the size of the world visible on the screen, in world units
+    pub(crate) fn visible_size(&self) -> Vector<f32> {
+        let size = self.screen_rect.size() / self.scale();
+
+        Vector::new(size.x, size.y)
+    }
+
+    /// Gets the top left and bottom right of the world visible on the screen
+    pub(crate) fn visible_bounds(&self) -> (Vector<f32>, Vector<f32>) {
+        let half_size = self.visible_size() / 2.0;
+
+        (self.center - half_size, self.center + half_size)
+    }
+
+    /// Converts world x to screen x
+    pub(crate) fn x_to_screen(&self, x: f32) -> f32 {
+        (x - self.center.x) * self.scale() + self.screen_rect.center().x
+    }
+
+    /// Converts world y to screen y
+    pub(crate) fn y_to_screen(&self, y: f32) -> f32 {
+        (y - self.center.y) * self.scale() + self.screen_rect.center().y
+    }
+
+    /// Converts a world position to a screen position
+    pub(crate) fn to_screen(&self, position: &Vector<f32>) -> Pos2 {
+        Pos2::new(self.x_to_screen(position.x), self.y_to_screen(position.y))
+    }
+
+    /// Converts a length in world units to screen pixels
+    pub(crate) fn length_to_screen(&self, length: f32) -> f32 {
+        length * self.scale()
+    }
+
+    /// Converts a screen position to a world position
+    pub(crate) fn to_world(&self, position: Pos2) -> Vector<f32> {
+        let offset = (position - self.screen_rect.center()) / self.scale();
+
+        Vector::new(self.center.x + offset.x, self.center.y + offset.y)
+    }
+
+    /// Centers the camera on a position, without showing anything below the bottom of the world
+    pub(crate) fn look_at(&mut self, position: Vector<f32>) {
+        let lowest_center_y = WORLD_Y_SIZE - self.visible_size().y / 2.0;
+
+        self.center = Vector::new(position.x, position.y.min(lowest_center_y));
+    }
+
+    /// Looks at a leader so its furthest x is two thirds of the way across the screen
+    pub(crate) fn look_at_leader(&mut self, max_x: f32, y: f32) {
+        let offset = self.visible_size().x * (LEADER_SCREEN_FRACTION - 0.5);
+
+        self.look_at(Vector::new(max_x - offset, y));
+    }
+
+    /// Moves the camera to keep what it's following in view, out of a set of creatures and the selected one.
+    /// Falls back to following the leader if nothing is selected
+    pub(crate) fn follow<V: SimulationView>(&mut self, views: &[V], selected: Option<&V>) {
+        if views.is_empty() {
+            return;
+        }
+
+        let center_of = |view: &V| {
+            let (top_left, bottom_right) = view.get_bounds();
+
+            (top_left + bottom_right) / 2.0
+        };
+
+        match (self.follow_mode, selected) {
+            (FollowMode::Fixed, _) => {}
+            (FollowMode::Selected, Some(view)) => self.look_at(center_of(view)),
+            (FollowMode::Centroid, _) => {
+                let sum = views
+                    .iter()
+                    .map(center_of)
+                    .fold(Vector::zeros(), |sum, center| sum + center);
+
+                self.look_at(sum / views.len() as f32);
+            }
+            (FollowMode::Leader, _) | (FollowMode::Selected, None) => {
+                let (top_left, bottom_right) = views
+                    .iter()
+                    .map(|view| view.get_bounds())
+                    .max_by(|(_, a), (_, b)| util::cmp_f32(&a.x, &b.x))
+                    .unwrap();
+
+                self.look_at_leader(bottom_right.x, (top_left.y + bottom_right.y) / 2.0);
+            }
+        }
+    }
+
+    /// Gets the score lines in view, leaving out the smaller ones and their labels when zoomed out too far to
+    /// tell them apart
+    pub(crate) fn score_lines(&self) -> Vec<ScoreLine> {
+        let (top_left, bottom_right) = self.visible_bounds();
+        let spacing =
+            self.length_to_screen(Simulation::score_to_x(1.0) - Simulation::score_to_x(0.0));
+        let minor_every = SCORE_PER_SCREEN / 2;
+        let first = Simulation::x_to_score(top_left.x).floor() as i32 - 1;
+        let last = Simulation::x_to_score(bottom_right.x).ceil() as i32 + 1;
+
+        (first..=last)
+            .filter_map(|score| {
+                let minor = score % minor_every == 0;
+                let major = score % SCORE_PER_SCREEN == 0;
+
+                let (height_scale, line_spacing) = if major {
+                    (2.0 / 3.0, spacing * SCORE_PER_SCREEN as f32)
+                } else if minor {
+                    (1.0 / 3.0, spacing * minor_every as f32)
+                } else {
+                    (0.25, spacing)
+                };
+
+                if line_spacing < MIN_SCORE_LINE_SPACING {
+                    return None;
+                }
+
+                Some(ScoreLine {
+                    score,
+                    x: Simulation::score_to_x(score as f32),
+                    height_scale,
+                    labeled: (minor || major) && line_spacing >= MIN_SCORE_LABEL_SPACING,
+                })
+            })
+            .collect()
+    }
+
+    /// Moves the camera by a distance in screen pixels, like dragging the world
+    pub(crate) fn pan(&mut self, screen_delta: Vec2) {
+        let delta = screen_delta / self.scale();
+
+        self.center -= Vector::new(delta.x, delta.y);
+    }
+
+    /// Zooms in (factor > 1) or out (factor < 1), keeping the world under a screen position where it is
+    pub(crate) fn zoom_around(&mut self, screen_position: Pos2, factor: f32) {
+        let before = self.to_world(screen_position);
+
+        self.zoom = (self.zoom * factor).clamp(*ZOOM_RANGE.start(), *ZOOM_RANGE.end());
+
+        let after = self.to_world(screen_position);
+
+        self.center += Vector::new(before.x - after.x, before.y - after.y);
+    }
+}
+
+impl Default for Camera {
+    /// A camera drawing to a screen the size of the world
+    fn default() -> Self {
+        Self::new(Vec2::new(WORLD_X_SIZE, WORLD_Y_SIZE))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    pub fn camera() {
+        let mut camera = Camera::new(Vec2::new(1600.0, 560.0));
+
+        // Scaled by the smaller ratio, the same on both axes
+        assert_eq!(camera.scale(), 1.0);
+        assert_eq!(
+            camera.to_screen(&Vector::new(WORLD_X_SIZE / 2.0, WORLD_Y_SIZE / 2.0)),
+            Pos2::new(800.0, 280.0)
+        );
+
+        let pointer = Pos2::new(1200.0, 100.0);
+        let under_pointer = camera.to_world(pointer);
+
+        camera.zoom_around(pointer, 2.0);
+
+        assert_eq!(camera.scale(), 2.0);
+        assert!((camera.to_world(pointer) - under_pointer).norm() < 0.001);
+
+        camera.pan(Vec2::new(10.0, 0.0));
+
+        assert!((camera.to_world(pointer).x - (under_pointer.x - 5.0)).abs() < 0.001);
+
+        camera.fit_height(100.0);
+
+        assert!((camera.visible_size().y - 100.0).abs() < 0.001);
+
+        camera.rest_on_floor(0.75);
+
+        assert!((camera.y_to_screen(FLOOR_TOP_Y) - 420.0).abs() < 0.001);
+
+        // Never looks below the world
+        camera.look_at(Vector::new(0.0, WORLD_Y_SIZE));
+
+        assert!(camera.visible_bounds().1.y <= WORLD_Y_SIZE + 0.001);
+    }
+}
@@ -0,0 +1,469 @@
+//! Comparison mode, which runs a few creatures side by side in stacked lanes with the same clock
+
+use std::{
+    io::ErrorKind,
+    mem,
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use eframe::egui;
+use egui::{Pos2, Rect, RichText, ScrollArea, Stroke, Vec2};
+
+use super::{paint_text, App, AppState, Camera, Emphasis, TEXT_COLOR, WHITE};
+use crate::{
+    ancestry::short_id,
+    creature::{Creature, CreatureBuilder, Position, CREATURES_DIRECTORY},
+    hall_of_fame::HallOfFame,
+    simulation::{
+        Environment, Simulation, SimulationView, FLOOR_TOP_Y, STEPS_FREQUENCY, WORLD_X_SIZE,
+    },
+    util,
+};
+
+/// The most creatures that can be compared at once
+pub(crate) const MAX_CONTESTANTS: usize = 4;
+const MIN_CONTESTANTS: usize = 2;
+const PANEL_WIDTH: f32 = 360.0;
+const LIST_HEIGHT: f32 = 200.0;
+const LANE_TEXT_SIZE: f32 = 25.0;
+const LANE_DIVIDER_THICKNESS: f32 = 2.0;
+
+/// A creature picked to be compared, with where it came from
+#[derive(Clone)]
+struct Contestant {
+    label: String,
+    creature: Creature,
+}
+
+/// A contestant being simulated, with a camera of its own
+struct Lane {
+    label: String,
+    simulation: Simulation,
+    camera: Camera,
+}
+
+/// How a contestant did, for the results table
+#[derive(Debug, Clone, PartialEq)]
+struct Standing {
+    label: String,
+    score: f32,
+    energy: f32,
+    efficiency: f32,
+}
+
+/// The state of comparison mode
+#[derive(Default)]
+pub(crate) struct Comparison {
+    /// The creatures to compare, in lane order
+    contestants: Vec<Contestant>,
+    /// Empty until the comparison is started
+    lanes: Vec<Lane>,
+    environment: Environment,
+    /// How many steps each lane runs for in total
+    steps: i32,
+    steps_left: i32,
+    time_left_over: Duration,
+    paused: bool,
+    /// Loaded when the screen is opened
+    hall_of_fame: Option<HallOfFame>,
+    saved_creatures: Vec<Creature>,
+    /// Where to go back to
+    return_state: AppState,
+    message: Option<String>,
+}
+
+impl Comparison {
+    /// Gets how many creatures have been picked
+    pub(crate) fn contestant_count(&self) -> usize {
+        self.contestants.len()
+    }
+
+    /// Picks a creature to be compared, failing if there are already as many as can be compared
+    pub(crate) fn add(&mut self, label: String, creature: &Creature) -> Result<(), String> {
+        if self.contestants.len() >= MAX_CONTESTANTS {
+            return Err(format!(
+                "At most {} creatures can be compared",
+                MAX_CONTESTANTS
+            ));
+        }
+
+        self.contestants.push(Contestant {
+            label,
+            creature: creature.clone(),
+        });
+
+        Ok(())
+    }
+
+    /// Loads the places creatures can be picked from, to come back to a state once done. What fails to load is
+    /// described in the message
+    fn open(&mut self, return_state: AppState) {
+        let mut problems = Vec::new();
+
+        self.return_state = return_state;
+        self.hall_of_fame = match HallOfFame::load(&HallOfFame::default_path()) {
+            Ok(hall_of_fame) => Some(hall_of_fame),
+            Err(error) => {
+                problems.push(format!("Failed to load the hall of fame: {}", error));
+                None
+            }
+        };
+        self.saved_creatures = match Creature::load_directory(Path::new(CREATURES_DIRECTORY)) {
+            Ok(creatures) => creatures,
+            // Nothing has been saved yet
+            Err(error) if error.kind() == ErrorKind::NotFound => Vec::new(),
+            Err(error) => {
+                problems.push(format!("Failed to load saved creatures: {}", error));
+                Vec::new()
+            }
+        };
+        self.message = if problems.is_empty() {
+            None
+        } else {
+            Some(problems.join("\n"))
+        };
+    }
+
+    /// Starts simulating every contestant from the beginning in the same [Environment], for a number of steps
+    fn start(&mut self, environment: Environment, steps: i32) {
+        let bottom_center = Position::new(WORLD_X_SIZE / 2.0, FLOOR_TOP_Y);
+
+        self.lanes = self
+            .contestants
+            .iter()
+            .map(|contestant| Lane {
+                label: contestant.label.clone(),
+                simulation: Simulation::with_environment(
+                    CreatureBuilder::copy(&contestant.creature)
+                        .translate_bottom_center_to(&bottom_center)
+                        .build(),
+                    environment,
+                ),
+                camera: Camera::default(),
+            })
+            .collect();
+        self.environment = environment;
+        self.steps = steps;
+        self.steps_left = steps;
+        self.time_left_over = Duration::ZERO;
+        self.paused = false;
+    }
+
+    /// Steps every lane together for a certain amount of time, stopping once they've run for long enough
+    fn run(&mut self, mut time: Duration) {
+        if self.paused {
+            return;
+        }
+
+        time += self.time_left_over;
+
+        while time > STEPS_FREQUENCY && self.steps_left > 0 {
+            time -= STEPS_FREQUENCY;
+            self.steps_left -= 1;
+
+            for lane in &mut self.lanes {
+                lane.simulation.step();
+            }
+        }
+
+        self.time_left_over = time;
+    }
+
+    /// Gets how every lane is doing, furthest first
+    fn standings(&self) -> Vec<Standing> {
+        let mut standings: Vec<Standing> = self
+            .lanes
+            .iter()
+            .map(|lane| Standing {
+                label: lane.label.clone(),
+                score: lane.simulation.get_score(),
+                energy: lane.simulation.get_total_energy(),
+                efficiency: lane.simulation.get_efficiency(),
+            })
+            .collect();
+
+        standings.sort_by(|a, b| util::cmp_f32(&b.score, &a.score));
+
+        standings
+    }
+}
+
+impl App {
+    /// Opens comparison mode, going back to the current screen when done
+    pub(super) fn open_comparison(&mut self) {
+        let return_state = mem::take(&mut self.state);
+
+        self.comparison.open(return_state);
+        self.state = AppState::Comparison;
+        self.last_frame = None;
+    }
+
+    /// Renders comparison mode
+    pub(super) fn render_comparison(&mut self, ui: &mut egui::Ui) {
+        let now = Instant::now();
+        let mut comparison = mem::take(&mut self.comparison);
+
+        if let Some(last_frame) = self.last_frame {
+            comparison.run(now.duration_since(last_frame));
+        }
+
+        self.last_frame = Some(now);
+
+        egui::SidePanel::right("Comparison panel")
+            .resizable(false)
+            .default_width(PANEL_WIDTH)
+            .show_inside(ui, |ui| self.render_comparison_panel(ui, &mut comparison));
+
+        self.paint_lanes(ui, &mut comparison.lanes);
+
+        self.comparison = comparison;
+    }
+
+    /// Paints each lane in its own strip of the screen, stacked from top to bottom
+    fn paint_lanes(&mut self, ui: &egui::Ui, lanes: &mut [Lane]) {
+        let area = ui.available_rect_before_wrap();
+
+        if lanes.is_empty() {
+            self.camera.set_screen_rect(area);
+            self.camera.reset();
+            self.paint_scenery(self.setup.settings().environment.terrain, ui.painter());
+            return;
+        }
+
+        let lane_height = area.height() / lanes.len() as f32;
+
+        for (index, lane) in lanes.iter_mut().enumerate() {
+            let rect = Rect::from_min_size(
+                Pos2::new(area.min.x, area.min.y + index as f32 * lane_height),
+                Vec2::new(area.width(), lane_height),
+            );
+            let painter = ui.painter().with_clip_rect(rect);
+
+            lane.camera.set_screen_rect(rect);
+            lane.camera.follow(
+                std::slice::from_ref(&lane.simulation),
+                Some(&lane.simulation),
+            );
+
+            // Painting uses the app's camera, so the lane's is swapped in
+            mem::swap(&mut self.camera, &mut lane.camera);
+            self.paint_scenery(lane.simulation.terrain(), &painter);
+            self.paint_simulation(&lane.simulation, Emphasis::Normal, &painter);
+            mem::swap(&mut self.camera, &mut lane.camera);
+
+            paint_text(
+                format!("{}: {:.2}m", lane.label, lane.simulation.get_score()),
+                rect.min + Vec2::new(10.0, 5.0),
+                LANE_TEXT_SIZE,
+                WHITE,
+                false,
+                &painter,
+            );
+
+            if index > 0 {
+                painter.line_segment(
+                    [rect.left_top(), rect.right_top()],
+                    Stroke::new(LANE_DIVIDER_THICKNESS, WHITE),
+                );
+            }
+        }
+    }
+
+    /// Renders the creatures picked to be compared and where more can be picked from, or the results once
+    /// the comparison has started
+    fn render_comparison_panel(&mut self, ui: &mut egui::Ui, comparison: &mut Comparison) {
+        ui.heading("Compare creatures");
+        ui.add_space(5.0);
+
+        if comparison.lanes.is_empty() {
+            self.render_contestant_picker(ui, comparison);
+        } else {
+            Self::render_comparison_results(ui, comparison);
+        }
+
+        if let Some(message) = &comparison.message {
+            ui.label(RichText::new(message).color(TEXT_COLOR));
+        }
+
+        ui.add_space(10.0);
+
+        if ui.button("Back").clicked() {
+            comparison.lanes.clear();
+            self.state = mem::take(&mut comparison.return_state);
+            self.last_frame = None;
+        }
+    }
+
+    /// Renders the picked creatures and the lists of creatures that can be picked
+    fn render_contestant_picker(&mut self, ui: &mut egui::Ui, comparison: &mut Comparison) {
+        ui.label(format!(
+            "Picked {} of {}. Creatures in the current generation can be added from the inspector",
+            comparison.contestants.len(),
+            MAX_CONTESTANTS
+        ));
+
+        let mut removed = None;
+
+        for (index, contestant) in comparison.contestants.iter().enumerate() {
+            ui.horizontal(|ui| {
+                ui.label(format!("{}. {}", index + 1, contestant.label));
+
+                if ui.small_button("Remove").clicked() {
+                    removed = Some(index);
+                }
+            });
+        }
+
+        if let Some(index) = removed {
+            comparison.contestants.remove(index);
+        }
+
+        let ready = (MIN_CONTESTANTS..=MAX_CONTESTANTS).contains(&comparison.contestants.len());
+
+        if ui
+            .add_enabled(ready, egui::Button::new("Start"))
+            .on_disabled_hover_text(format!("Pick at least {} creatures", MIN_CONTESTANTS))
+            .clicked()
+        {
+            // Everyone runs in the environment and for the time of the last run setup, to keep it fair
+            let settings = self.setup.settings();
+
+            comparison.start(settings.environment, settings.steps_per_generation());
+            comparison.message = None;
+        }
+
+        ui.separator();
+
+        let mut picked = None;
+
+        egui::CollapsingHeader::new("Hall of fame")
+            .default_open(true)
+            .show(ui, |ui| match &comparison.hall_of_fame {
+                Some(hall_of_fame) if !hall_of_fame.all_time().is_empty() => {
+                    ScrollArea::vertical()
+                        .id_source("Hall of fame picks")
+                        .max_height(LIST_HEIGHT)
+                        .show(ui, |ui| {
+                            for (index, entry) in hall_of_fame.all_time().iter().enumerate() {
+                                let label = format!("Hall of fame #{}", index + 1);
+                                let text = format!("{} ({:.2}m)", label, entry.score);
+
+                                if ui.selectable_label(false, text).clicked() {
+                                    picked = Some((label, entry.creature.clone()));
+                                }
+                            }
+                        });
+                }
+                _ => {
+                    ui.label("Nothing in the hall of fame yet");
+                }
+            });
+
+        egui::CollapsingHeader::new(format!("Saved in {}", CREATURES_DIRECTORY))
+            .default_open(true)
+            .show(ui, |ui| {
+                if comparison.saved_creatures.is_empty() {
+                    ui.label("No saved creatures");
+                }
+
+                ScrollArea::vertical()
+                    .id_source("Saved creature picks")
+                    .max_height(LIST_HEIGHT)
+                    .show(ui, |ui| {
+                        for creature in &comparison.saved_creatures {
+                            let label = format!("Saved {}", short_id(creature.id()));
+
+                            if ui.selectable_label(false, &label).clicked() {
+                                picked = Some((label, creature.clone()));
+                            }
+                        }
+                    });
+            });
+
+        if let Some((label, creature)) = picked {
+            if let Err(message) = comparison.add(label, &creature) {
+                comparison.message = Some(message);
+            }
+        }
+    }
+
+    /// Renders how each lane is doing, and the controls for the running comparison
+    fn render_comparison_results(ui: &mut egui::Ui, comparison: &mut Comparison) {
+        let status = if comparison.steps_left > 0 {
+            "Running..."
+        } else {
+            "Final results"
+        };
+
+        ui.strong(status);
+
+        egui::Grid::new("Comparison results")
+            .num_columns(5)
+            .striped(true)
+            .show(ui, |ui| {
+                for header in ["Rank", "Creature", "Distance", "Energy", "Efficiency"] {
+                    ui.strong(header);
+                }
+                ui.end_row();
+
+                for (index, standing) in comparison.standings().iter().enumerate() {
+                    ui.label((index + 1).to_string());
+                    ui.label(&standing.label);
+                    ui.label(format!("{:.2}m", standing.score));
+                    ui.label(format!("{:.1}", standing.energy));
+                    ui.label(format!("{:.3}", standing.efficiency));
+                    ui.end_row();
+                }
+            });
+
+        ui.horizontal(|ui| {
+            let pause_text = if comparison.paused { "Resume" } else { "Pause" };
+
+            if ui.button(pause_text).clicked() {
+                comparison.paused = !comparison.paused;
+            }
+
+            if ui.button("Restart").clicked() {
+                comparison.start(comparison.environment, comparison.steps);
+            }
+
+            if ui.button("Change creatures").clicked() {
+                comparison.lanes.clear();
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    pub fn comparison() {
+        let mut comparison = Comparison::default();
+
+        for index in 0..MAX_CONTESTANTS {
+            comparison
+                .add(index.to_string(), &CreatureBuilder::random().build())
+                .unwrap();
+        }
+
+        assert!(comparison
+            .add("Too many".to_owned(), &CreatureBuilder::random().build())
+            .is_err());
+
+        comparison.start(Environment::default(), 10);
+        comparison.run(STEPS_FREQUENCY * 20);
+
+        // Every lane stops together once the steps run out
+        assert_eq!(comparison.steps_left, 0);
+        assert_eq!(comparison.lanes.len(), MAX_CONTESTANTS);
+
+        let standings = comparison.standings();
+
+        assert!(standings
+            .windows(2)
+            .all(|pair| pair[0].score >= pair[1].score));
+    }
+}
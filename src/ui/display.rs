@@ -0,0 +1,377 @@
+//! Options for which creatures of a generation are painted and how, set from a side panel
+
+use eframe::egui;
+use egui::{DragValue, Pos2, Rect, RichText, Vec2};
+
+use super::{App, Emphasis};
+use crate::{
+    evolver::{CreatureSnapshot, Snapshot},
+    simulation::SimulationView,
+    util,
+};
+
+const DEFAULT_LEADERS: usize = 10;
+const DEFAULT_LAGGARD_DISTANCE: f32 = 5.0;
+const DEFAULT_CELLS: usize = 6;
+const MAX_CELLS: usize = 25;
+
+/// Which creatures of a generation are painted and how
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct DisplayOptions {
+    /// How many of the creatures with the best current score are leaders
+    leaders: usize,
+    others: OthersDisplay,
+    /// Whether creatures more than [DisplayOptions::laggard_distance] behind the best score are hidden
+    hide_laggards: bool,
+    laggard_distance: f32,
+    highlight: Highlight,
+    layout: Layout,
+    /// How many of the furthest shown creatures get a cell of their own, unless they're overlapping
+    cells: usize,
+}
+
+impl DisplayOptions {
+    /// Gets how the shown creatures are laid out
+    pub(crate) fn layout(&self) -> Layout {
+        self.layout
+    }
+
+    /// Gets how many of the furthest shown creatures get a cell of their own
+    pub(crate) fn cells(&self) -> usize {
+        self.cells
+    }
+
+    /// Decides how each creature is painted from their current scores and whether they're highlighted, or
+    /// [None] if they're hidden. Highlighted creatures are always shown
+    pub(crate) fn emphases(&self, scores: &[f32], highlighted: &[bool]) -> Vec<Option<Emphasis>> {
+        let mut by_score: Vec<usize> = (0..scores.len()).collect();
+        by_score.sort_by(|a, b| util::cmp_f32(&scores[*b], &scores[*a]));
+
+        let mut is_leader = vec![false; scores.len()];
+
+        for index in by_score.iter().take(self.leaders.max(1)) {
+            is_leader[*index] = true;
+        }
+
+        let best = by_score.first().map(|index| scores[*index]);
+
+        scores
+            .iter()
+            .zip(is_leader)
+            .zip(highlighted)
+            .map(|((score, is_leader), is_highlighted)| {
+                let is_laggard = self.hide_laggards
+                    && best.map_or(false, |best| *score < best - self.laggard_distance);
+
+                if *is_highlighted {
+                    Some(Emphasis::Highlighted)
+                } else if is_laggard {
+                    None
+                } else if is_leader {
+                    Some(Emphasis::Normal)
+                } else {
+                    match self.others {
+                        OthersDisplay::Shown => Some(Emphasis::Normal),
+                        OthersDisplay::Ghosted => Some(Emphasis::Ghost),
+                        OthersDisplay::Hidden => None,
+                    }
+                }
+            })
+            .collect()
+    }
+
+    /// Decides how each creature of a snapshot's current generation is painted, see [DisplayOptions::emphases]
+    pub(crate) fn snapshot_emphases(
+        &self,
+        snapshot: &Snapshot,
+        generation: &[CreatureSnapshot],
+    ) -> Vec<Option<Emphasis>> {
+        let scores: Vec<f32> = generation.iter().map(|view| view.get_score()).collect();
+        let highlighted: Vec<bool> = match self.highlight {
+            Highlight::Nothing => vec![false; generation.len()],
+            Highlight::Elites => {
+                // The creatures that would survive if the generation ended now
+                let fitnesses: Vec<f32> =
+                    generation.iter().map(|view| view.get_fitness()).collect();
+                let mut is_elite = vec![false; generation.len()];
+
+                for index in snapshot.settings().select(&fitnesses).survivors {
+                    is_elite[index] = true;
+                }
+
+                is_elite
+            }
+            Highlight::Champion => {
+                let champion = snapshot.best_replay().map(|replay| *replay.creature().id());
+
+                generation
+                    .iter()
+                    .map(|view| {
+                        champion.is_some() && view.creature().parent_id() == champion.as_ref()
+                    })
+                    .collect()
+            }
+        };
+
+        self.emphases(&scores, &highlighted)
+    }
+}
+
+impl Default for DisplayOptions {
+    fn default() -> Self {
+        DisplayOptions {
+            leaders: DEFAULT_LEADERS,
+            others: OthersDisplay::Shown,
+            hide_laggards: false,
+            laggard_distance: DEFAULT_LAGGARD_DISTANCE,
+            highlight: Highlight::Nothing,
+            layout: Layout::Overlapping,
+            cells: DEFAULT_CELLS,
+        }
+    }
+}
+
+/// How creatures that aren't leaders are painted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum OthersDisplay {
+    Shown,
+    Ghosted,
+    Hidden,
+}
+
+impl OthersDisplay {
+    /// Every option, in the order they're shown
+    pub(crate) const ALL: [OthersDisplay; 3] = [
+        OthersDisplay::Shown,
+        OthersDisplay::Ghosted,
+        OthersDisplay::Hidden,
+    ];
+
+    /// The display name of the option
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            OthersDisplay::Shown => "Shown",
+            OthersDisplay::Ghosted => "Ghosts",
+            OthersDisplay::Hidden => "Hidden",
+        }
+    }
+}
+
+/// Which creatures stand out from the rest
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Highlight {
+    Nothing,
+    /// The creatures that would survive if the generation ended now
+    Elites,
+    /// The offspring of the best creature of the last generation
+    Champion,
+}
+
+impl Highlight {
+    /// Every option, in the order they're shown
+    pub(crate) const ALL: [Highlight; 3] =
+        [Highlight::Nothing, Highlight::Elites, Highlight::Champion];
+
+    /// The display name of the option
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            Highlight::Nothing => "Nothing",
+            Highlight::Elites => "Elites",
+            Highlight::Champion => "Last champion's offspring",
+        }
+    }
+}
+
+/// How the shown creatures are laid out on the screen. The physics is the same either way
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Layout {
+    /// Every creature on the same ground, where they started
+    Overlapping,
+    /// A horizontal lane for each creature, stacked from the furthest down
+    Lanes,
+    /// A cell for each creature, in rows from the furthest
+    Grid,
+}
+
+impl Layout {
+    /// Every layout, in the order they're shown
+    pub(crate) const ALL: [Layout; 3] = [Layout::Overlapping, Layout::Lanes, Layout::Grid];
+
+    /// The display name of the layout
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            Layout::Overlapping => "Overlapping",
+            Layout::Lanes => "Lanes",
+            Layout::Grid => "Grid",
+        }
+    }
+
+    /// Splits an area into a cell for each of a number of creatures, or [None] if they share the area
+    pub(crate) fn cells(&self, area: Rect, count: usize) -> Option<Vec<Rect>> {
+        if count == 0 {
+            return None;
+        }
+
+        let (columns, rows) = match self {
+            Layout::Overlapping => return None,
+            Layout::Lanes => (1, count),
+            Layout::Grid => {
+                let columns = (count as f32).sqrt().ceil() as usize;
+
+                (columns, (count + columns - 1) / columns)
+            }
+        };
+        let size = Vec2::new(area.width() / columns as f32, area.height() / rows as f32);
+
+        Some(
+            (0..count)
+                .map(|index| {
+                    let min = Pos2::new(
+                        area.min.x + (index % columns) as f32 * size.x,
+                        area.min.y + (index / columns) as f32 * size.y,
+                    );
+
+                    Rect::from_min_size(min, size)
+                })
+                .collect(),
+        )
+    }
+}
+
+impl App {
+    /// Renders the controls for the [DisplayOptions]
+    pub(super) fn render_display_options(&mut self, ui: &mut egui::Ui) {
+        let population = self
+            .snapshot
+            .as_ref()
+            .map_or(1, |snapshot| snapshot.settings().population);
+        let display = &mut self.display;
+
+        ui.heading("Display");
+        ui.add_space(5.0);
+
+        ui.horizontal(|ui| {
+            ui.label("Leaders");
+            ui.add(DragValue::new(&mut display.leaders).clamp_range(1..=population.max(1)))
+                .on_hover_text("How many of the furthest creatures are always shown");
+        });
+
+        ui.label("Everyone else");
+        ui.horizontal(|ui| {
+            for option in OthersDisplay::ALL {
+                ui.selectable_value(&mut display.others, option, option.name());
+            }
+        });
+
+        ui.add_space(5.0);
+
+        ui.checkbox(&mut display.hide_laggards, "Hide creatures falling behind");
+        ui.add_enabled_ui(display.hide_laggards, |ui| {
+            ui.horizontal(|ui| {
+                ui.add(
+                    DragValue::new(&mut display.laggard_distance)
+                        .clamp_range(0.0..=1000.0)
+                        .speed(0.1)
+                        .suffix("m"),
+                );
+                ui.label("behind the leader");
+            });
+        });
+
+        ui.add_space(5.0);
+
+        ui.label("Layout");
+        ui.horizontal(|ui| {
+            for layout in Layout::ALL {
+                ui.selectable_value(&mut display.layout, layout, layout.name());
+            }
+        });
+        ui.add_enabled_ui(display.layout != Layout::Overlapping, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Cells");
+                ui.add(DragValue::new(&mut display.cells).clamp_range(1..=MAX_CELLS))
+                    .on_hover_text(
+                        "How many of the furthest shown creatures get a cell of their own",
+                    );
+            });
+        });
+
+        ui.add_space(5.0);
+
+        ui.label("Highlight");
+        for option in Highlight::ALL {
+            ui.radio_value(&mut display.highlight, option, option.name());
+        }
+
+        if display.highlight == Highlight::Elites {
+            ui.label(
+                RichText::new("The creatures that would survive if the generation ended now")
+                    .small(),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    pub fn emphases() {
+        let scores = [1.0, 9.0, 3.0, 10.0];
+        let mut options = DisplayOptions {
+            leaders: 2,
+            others: OthersDisplay::Ghosted,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            options.emphases(&scores, &[false; 4]),
+            vec![
+                Some(Emphasis::Ghost),
+                Some(Emphasis::Normal),
+                Some(Emphasis::Ghost),
+                Some(Emphasis::Normal)
+            ]
+        );
+
+        options.others = OthersDisplay::Shown;
+        options.hide_laggards = true;
+        options.laggard_distance = 8.0;
+
+        // Highlighted creatures are shown even when falling behind
+        assert_eq!(
+            options.emphases(&scores, &[false, false, true, false]),
+            vec![
+                None,
+                Some(Emphasis::Normal),
+                Some(Emphasis::Highlighted),
+                Some(Emphasis::Normal)
+            ]
+        );
+    }
+
+    #[test]
+    pub fn layout() {
+        let area = Rect::from_min_size(Pos2::new(10.0, 20.0), Vec2::new(300.0, 200.0));
+
+        assert_eq!(Layout::Overlapping.cells(area, 5), None);
+        assert_eq!(Layout::Lanes.cells(area, 0), None);
+
+        let lanes = Layout::Lanes.cells(area, 4).unwrap();
+
+        assert_eq!(lanes.len(), 4);
+        assert_eq!(
+            lanes[1],
+            Rect::from_min_size(Pos2::new(10.0, 70.0), Vec2::new(300.0, 50.0))
+        );
+
+        // Five creatures fit in three columns of two rows, filling the first row first
+        let grid = Layout::Grid.cells(area, 5).unwrap();
+
+        assert_eq!(grid.len(), 5);
+        assert_eq!(grid[0].size(), Vec2::new(100.0, 100.0));
+        assert_eq!(grid[2].min, Pos2::new(210.0, 20.0));
+        assert_eq!(grid[4].min, Pos2::new(110.0, 120.0));
+    }
+}
@@ -0,0 +1,606 @@
+//! The creature editor, where creatures are designed by hand
+
+use std::{collections::HashMap, mem, ops::RangeInclusive, time::Duration, time::Instant};
+
+use eframe::{egui, epaint::CircleShape};
+use egui::{Color32, Key, Painter, Pos2, RichText, Sense, Slider, Stroke};
+use rapier::prelude::Vector;
+use uuid::Uuid;
+
+use super::{
+    muscle_thickness, App, AppState, Emphasis, SELECTED_OUTLINE_COLOR, SELECTED_OUTLINE_THICKNESS,
+    SELECTION_TOLERANCE,
+};
+use crate::{
+    creature::{
+        Creature, CreatureBuilder, CreatureColors, MovementParameters, Muscle, Node, Position,
+        Waveform, CONTRACTION_PERIOD_RANGE, EXTENSION_PERIOD_RANGE,
+    },
+    simulation::{
+        Simulation, SimulationView, Terrain, FLOOR_TOP_Y, STEPS_FREQUENCY, STEPS_PER_SECOND,
+        WORLD_X_SIZE,
+    },
+    util,
+};
+
+const NODE_SIZE_RANGE: RangeInclusive<f32> = 5.0..=30.0;
+const DEFAULT_NODE_SIZE: f32 = 15.0;
+const DEFAULT_PERIOD: i32 = STEPS_PER_SECOND;
+const PANEL_WIDTH: f32 = 300.0;
+
+/// What is selected in the editor
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Selection {
+    Node(Uuid),
+    Muscle(Uuid),
+}
+
+/// A creature being designed by hand, along with the editor's state
+pub(super) struct Editor {
+    nodes: HashMap<Uuid, Node>,
+    muscles: HashMap<Uuid, Muscle>,
+    movement_parameters: HashMap<Uuid, MovementParameters>,
+    colors: CreatureColors,
+    selection: Option<Selection>,
+    new_node_size: f32,
+    muscle_start: Option<Uuid>,
+    test_run: Option<Simulation>,
+    time_left_over: Duration,
+    message: Option<String>,
+}
+
+impl Editor {
+    /// Creates an editor with an empty design
+    pub(super) fn new() -> Editor {
+        Editor {
+            nodes: HashMap::new(),
+            muscles: HashMap::new(),
+            movement_parameters: HashMap::new(),
+            colors: CreatureColors::new(),
+            selection: None,
+            new_node_size: DEFAULT_NODE_SIZE,
+            muscle_start: None,
+            test_run: None,
+            time_left_over: Duration::ZERO,
+            message: None,
+        }
+    }
+
+    /// Adds a node at a position, returning its id
+    fn add_node(&mut self, position: Position, size: f32) -> Uuid {
+        let node = Node::new(position, size);
+        let id = node.id;
+
+        self.nodes.insert(id, node);
+
+        id
+    }
+
+    /// Connects two nodes with a muscle that rests at their current distance, returning its id. Does nothing
+    /// if the nodes are the same, missing or already connected
+    fn add_muscle(&mut self, from_id: Uuid, to_id: Uuid) -> Option<Uuid> {
+        let already_connected = self.muscles.values().any(|muscle| {
+            (muscle.from_id == from_id && muscle.to_id == to_id)
+                || (muscle.from_id == to_id && muscle.to_id == from_id)
+        });
+
+        if from_id == to_id || already_connected {
+            return None;
+        }
+
+        let length = self
+            .nodes
+            .get(&from_id)?
+            .position
+            .distance_to(&self.nodes.get(&to_id)?.position);
+        let muscle = Muscle::new(from_id, to_id);
+        let id = muscle.id;
+
+        self.movement_parameters.insert(
+            id,
+            MovementParameters::new(length, DEFAULT_PERIOD, DEFAULT_PERIOD, 0, Waveform::Sine),
+        );
+        self.muscles.insert(id, muscle);
+
+        Some(id)
+    }
+
+    /// Removes a node along with every muscle attached to it
+    fn remove_node(&mut self, id: Uuid) {
+        let attached: Vec<Uuid> = self
+            .muscles
+            .values()
+            .filter(|muscle| muscle.from_id == id || muscle.to_id == id)
+            .map(|muscle| muscle.id)
+            .collect();
+
+        for muscle_id in attached {
+            self.remove_muscle(muscle_id);
+        }
+
+        self.nodes.remove(&id);
+
+        if self.selection == Some(Selection::Node(id)) {
+            self.selection = None;
+        }
+    }
+
+    /// Removes a muscle
+    fn remove_muscle(&mut self, id: Uuid) {
+        self.muscles.remove(&id);
+        self.movement_parameters.remove(&id);
+
+        if self.selection == Some(Selection::Muscle(id)) {
+            self.selection = None;
+        }
+    }
+
+    /// Gets the closest node within [SELECTION_TOLERANCE] of a position
+    fn node_at(&self, position: &Position) -> Option<Uuid> {
+        self.nodes
+            .values()
+            .map(|node| {
+                let distance = (node.position.distance_to(position) - node.size / 2.0).max(0.0);
+
+                (distance, node.id)
+            })
+            .filter(|(distance, _)| *distance <= SELECTION_TOLERANCE)
+            .min_by(|(a, _), (b, _)| util::cmp_f32(a, b))
+            .map(|(_, id)| id)
+    }
+
+    /// Gets the closest muscle within [SELECTION_TOLERANCE] of a position
+    fn muscle_at(&self, position: &Position) -> Option<Uuid> {
+        self.muscles
+            .values()
+            .map(|muscle| {
+                let from = &self.nodes[&muscle.from_id].position;
+                let to = &self.nodes[&muscle.to_id].position;
+
+                (distance_to_segment(position, from, to), muscle.id)
+            })
+            .filter(|(distance, _)| *distance <= SELECTION_TOLERANCE)
+            .min_by(|(a, _), (b, _)| util::cmp_f32(a, b))
+            .map(|(_, id)| id)
+    }
+
+    /// Builds the design into a [Creature], or [None] if there are no nodes yet
+    fn build(&self) -> Option<Creature> {
+        if self.nodes.is_empty() {
+            return None;
+        }
+
+        let mut builder = CreatureBuilder::new();
+
+        for node in self.nodes.values() {
+            builder = builder.add_node(node.clone());
+        }
+
+        for muscle in self.muscles.values() {
+            builder = builder.add_muscle(muscle.clone());
+        }
+
+        Some(
+            builder
+                .add_movement_parameters(self.movement_parameters.clone())
+                .add_colors(self.colors)
+                .build(),
+        )
+    }
+
+    /// Starts simulating the design on the ground in the middle of the world, returning false if there's
+    /// nothing to simulate
+    fn start_test_run(&mut self) -> bool {
+        let creature = match self.build() {
+            Some(creature) => creature,
+            None => return false,
+        };
+
+        let bottom_center = Position::new(WORLD_X_SIZE / 2.0, FLOOR_TOP_Y);
+
+        self.test_run = Some(Simulation::new(
+            CreatureBuilder::copy(&creature)
+                .translate_bottom_center_to(&bottom_center)
+                .build(),
+        ));
+        self.time_left_over = Duration::ZERO;
+
+        true
+    }
+
+    /// Runs the test run, if there is one, for a certain amount of time
+    fn run(&mut self, mut time: Duration) {
+        let simulation = match &mut self.test_run {
+            Some(simulation) => simulation,
+            None => return,
+        };
+
+        time += self.time_left_over;
+
+        while time > STEPS_FREQUENCY {
+            time -= STEPS_FREQUENCY;
+            simulation.step();
+        }
+
+        self.time_left_over = time;
+    }
+}
+
+impl Default for Editor {
+    /// Same as [Editor::new]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Gets the distance from a position to the closest point on the segment between two others
+fn distance_to_segment(position: &Position, from: &Position, to: &Position) -> f32 {
+    let (dx, dy) = (to.x - from.x, to.y - from.y);
+    let length_squared = (dx * dx + dy * dy).max(f32::EPSILON);
+    let t = (((position.x - from.x) * dx + (position.y - from.y) * dy) / length_squared)
+        .clamp(0.0, 1.0);
+
+    position.distance_to(&Position::new(from.x + t * dx, from.y + t * dy))
+}
+
+/// Converts steps to seconds
+fn to_seconds(steps: i32) -> f32 {
+    steps as f32 / STEPS_PER_SECOND as f32
+}
+
+/// Converts seconds to the closest number of steps
+fn to_steps(seconds: f32) -> i32 {
+    (seconds * STEPS_PER_SECOND as f32).round() as i32
+}
+
+/// Converts a range of steps to a range of seconds
+fn to_seconds_range(range: RangeInclusive<i32>) -> RangeInclusive<f32> {
+    to_seconds(*range.start())..=to_seconds(*range.end())
+}
+
+impl App {
+    /// Renders the creature editor
+    pub(super) fn render_editor(&mut self, ui: &mut egui::Ui) {
+        let now = Instant::now();
+        let mut editor = mem::take(&mut self.editor);
+
+        if let Some(last_frame) = self.last_frame {
+            editor.run(now.duration_since(last_frame));
+        }
+
+        self.last_frame = Some(now);
+
+        // Paint before the panel so the panel is drawn on top
+        match &editor.test_run {
+            Some(simulation) => {
+                self.camera
+                    .follow(std::slice::from_ref(simulation), Some(simulation));
+                self.paint_scenery(simulation.terrain(), ui.painter());
+                self.paint_simulation(simulation, Emphasis::Normal, ui.painter());
+            }
+            None => {
+                self.camera.reset();
+                self.paint_scenery(Terrain::Flat, ui.painter());
+                self.paint_design(&editor, ui.painter());
+            }
+        }
+
+        egui::SidePanel::right("Editor panel")
+            .resizable(false)
+            .default_width(PANEL_WIDTH)
+            .show_inside(ui, |ui| self.render_editor_panel(ui, &mut editor));
+
+        if editor.test_run.is_none() {
+            self.edit_design(ui, &mut editor);
+        }
+
+        self.editor = editor;
+    }
+
+    /// Paints the design being edited, highlighting the selection and the muscle being dragged out
+    fn paint_design(&self, editor: &Editor, painter: &Painter) {
+        let to_screen =
+            |position: &Position| self.camera.to_screen(&Vector::new(position.x, position.y));
+        let to_screen_length = |length: f32| self.camera.length_to_screen(length);
+
+        for muscle in editor.muscles.values() {
+            let from_position = &editor.nodes[&muscle.from_id].position;
+            let to_position = &editor.nodes[&muscle.to_id].position;
+            let from = to_screen(from_position);
+            let to = to_screen(to_position);
+            let length = from_position.distance_to(to_position);
+            let thickness = to_screen_length(muscle_thickness(length, length));
+
+            if editor.selection == Some(Selection::Muscle(muscle.id)) {
+                painter.line_segment(
+                    [from, to],
+                    Stroke::new(
+                        thickness + SELECTED_OUTLINE_THICKNESS * 2.0,
+                        SELECTED_OUTLINE_COLOR,
+                    ),
+                );
+            }
+
+            painter.line_segment(
+                [from, to],
+                Stroke::new(thickness, editor.colors.muscle_extended()),
+            );
+        }
+
+        // The muscle being dragged out
+        if let Some(start) = editor.muscle_start.and_then(|id| editor.nodes.get(&id)) {
+            if let Some(pointer) = painter.ctx().input().pointer.hover_pos() {
+                painter.line_segment(
+                    [to_screen(&start.position), pointer],
+                    Stroke::new(
+                        to_screen_length(muscle_thickness(1.0, 1.0)),
+                        editor.colors.muscle_contracted(),
+                    ),
+                );
+            }
+        }
+
+        for node in editor.nodes.values() {
+            let stroke = if editor.selection == Some(Selection::Node(node.id)) {
+                Stroke::new(SELECTED_OUTLINE_THICKNESS, SELECTED_OUTLINE_COLOR)
+            } else {
+                Stroke::none()
+            };
+
+            painter.add(CircleShape {
+                center: to_screen(&node.position),
+                radius: to_screen_length(node.size / 2.0),
+                fill: editor.colors.node(),
+                stroke,
+            });
+        }
+    }
+
+    /// Handles clicking and dragging in the scene to place, connect, select and delete parts
+    fn edit_design(&self, ui: &mut egui::Ui, editor: &mut Editor) {
+        let rect = ui.available_rect_before_wrap();
+        let response = ui.interact(rect, ui.id().with("Editor scene"), Sense::click_and_drag());
+        let (press_origin, latest_position, delete_pressed) = {
+            let input = ui.input();
+
+            (
+                input.pointer.press_origin(),
+                input.pointer.hover_pos(),
+                input.key_pressed(Key::Delete),
+            )
+        };
+        let to_world = |position: Pos2| {
+            let world_position = self.camera.to_world(position);
+
+            Position::new(world_position.x, world_position.y)
+        };
+
+        if response.drag_started() {
+            editor.muscle_start =
+                press_origin.and_then(|position| editor.node_at(&to_world(position)));
+        }
+
+        if response.drag_released() {
+            let end = latest_position.and_then(|position| editor.node_at(&to_world(position)));
+
+            if let (Some(start), Some(end)) = (editor.muscle_start.take(), end) {
+                if let Some(id) = editor.add_muscle(start, end) {
+                    editor.selection = Some(Selection::Muscle(id));
+                }
+            }
+        }
+
+        if response.clicked() {
+            if let Some(position) = latest_position.map(to_world) {
+                editor.selection = if let Some(id) = editor.node_at(&position) {
+                    Some(Selection::Node(id))
+                } else if let Some(id) = editor.muscle_at(&position) {
+                    Some(Selection::Muscle(id))
+                } else if position.y + editor.new_node_size / 2.0 <= FLOOR_TOP_Y {
+                    Some(Selection::Node(
+                        editor.add_node(position, editor.new_node_size),
+                    ))
+                } else {
+                    None
+                };
+            }
+        }
+
+        if delete_pressed {
+            match editor.selection {
+                Some(Selection::Node(id)) => editor.remove_node(id),
+                Some(Selection::Muscle(id)) => editor.remove_muscle(id),
+                None => {}
+            }
+        }
+    }
+
+    /// Renders the editor's side panel, for editing the selection and using the design
+    fn render_editor_panel(&mut self, ui: &mut egui::Ui, editor: &mut Editor) {
+        ui.heading("Creature editor");
+        ui.label(
+            "Click to place a node, and drag from one node to another to connect them with a muscle. \
+            Press Delete to remove what's selected.",
+        );
+        ui.separator();
+
+        let testing = editor.test_run.is_some();
+
+        ui.add_enabled_ui(!testing, |ui| match editor.selection {
+            None => {
+                ui.add(
+                    Slider::new(&mut editor.new_node_size, NODE_SIZE_RANGE).text("New node size"),
+                );
+            }
+            Some(Selection::Node(id)) => {
+                ui.strong("Node");
+
+                if let Some(node) = editor.nodes.get_mut(&id) {
+                    ui.add(Slider::new(&mut node.size, NODE_SIZE_RANGE).text("Size"));
+                }
+
+                if ui.button("Delete node").clicked() {
+                    editor.remove_node(id);
+                }
+            }
+            Some(Selection::Muscle(id)) => {
+                ui.strong("Muscle");
+
+                if let Some(movement_parameters) = editor.movement_parameters.get_mut(&id) {
+                    Self::edit_movement_parameters(ui, movement_parameters);
+                }
+
+                if ui.button("Delete muscle").clicked() {
+                    editor.remove_muscle(id);
+                }
+            }
+        });
+
+        ui.separator();
+        ui.label(format!(
+            "{} nodes, {} muscles",
+            editor.nodes.len(),
+            editor.muscles.len()
+        ));
+
+        if let Some(simulation) = &editor.test_run {
+            ui.label(format!("Distance: {:.2}m", simulation.get_score()));
+
+            if ui.button("Stop test run").clicked() {
+                editor.test_run = None;
+            }
+        } else if ui.button("Test run").clicked() && !editor.start_test_run() {
+            editor.message = Some("Place at least one node first".to_string());
+        }
+
+        if ui.button("Save").clicked() {
+            editor.message = Some(match editor.build() {
+                Some(creature) => {
+                    let path = creature.default_path();
+
+                    match creature.save(&path) {
+                        Ok(_) => format!("Saved to {}", path.display()),
+                        Err(error) => format!("Failed to save: {}", error),
+                    }
+                }
+                None => "Place at least one node first".to_string(),
+            });
+        }
+
+        if ui.button("Evolve from this design").clicked() {
+            match editor.build() {
+                Some(creature) => match self.setup.start_from(vec![creature]) {
+                    Ok(worker) => {
+                        self.start_run(worker);
+                        editor.test_run = None;
+                    }
+                    Err(message) => editor.message = Some(message),
+                },
+                None => editor.message = Some("Place at least one node first".to_string()),
+            }
+        }
+
+        if ui.button("Clear").clicked() {
+            *editor = Editor::new();
+        }
+
+        if ui.button("Return to menu").clicked() {
+            self.state = AppState::MainMenu;
+            self.last_frame = None;
+            editor.test_run = None;
+        }
+
+        if let Some(message) = &editor.message {
+            ui.label(RichText::new(message).color(Color32::DARK_GRAY));
+        }
+    }
+
+    /// Renders controls for a muscle's movement parameters, replacing them when anything is changed
+    fn edit_movement_parameters(ui: &mut egui::Ui, movement_parameters: &mut MovementParameters) {
+        let mut extension = to_seconds(movement_parameters.extension_period());
+        let mut contraction = to_seconds(movement_parameters.contraction_period());
+        let mut phase = to_seconds(movement_parameters.phase_offset());
+        let mut waveform = movement_parameters.waveform().clone();
+        let mut changed = false;
+
+        changed |= ui
+            .add(
+                Slider::new(&mut extension, to_seconds_range(EXTENSION_PERIOD_RANGE))
+                    .text("Extension")
+                    .suffix("s"),
+            )
+            .changed();
+        changed |= ui
+            .add(
+                Slider::new(&mut contraction, to_seconds_range(CONTRACTION_PERIOD_RANGE))
+                    .text("Contraction")
+                    .suffix("s"),
+            )
+            .changed();
+        changed |= ui
+            .add(
+                Slider::new(&mut phase, 0.0..=extension + contraction)
+                    .text("Phase")
+                    .suffix("s"),
+            )
+            .changed();
+
+        egui::ComboBox::from_label("Waveform")
+            .selected_text(waveform.name())
+            .show_ui(ui, |ui| {
+                for option in [Waveform::Sine, Waveform::Square, Waveform::Triangle] {
+                    let name = option.name();
+
+                    changed |= ui.selectable_value(&mut waveform, option, name).changed();
+                }
+            });
+
+        if changed {
+            *movement_parameters = MovementParameters::new(
+                movement_parameters.muscle_length(),
+                to_steps(extension),
+                to_steps(contraction),
+                to_steps(phase),
+                waveform,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    pub fn edit_design() {
+        let mut editor = Editor::new();
+
+        assert!(editor.build().is_none());
+
+        let a = editor.add_node(Position::new(100.0, 100.0), 10.0);
+        let b = editor.add_node(Position::new(130.0, 100.0), 10.0);
+        let c = editor.add_node(Position::new(100.0, 140.0), 10.0);
+
+        let muscle = editor.add_muscle(a, b).unwrap();
+
+        assert!(editor.add_muscle(b, a).is_none());
+        assert!(editor.add_muscle(a, a).is_none());
+        assert!(editor.add_muscle(b, c).is_some());
+        assert_eq!(editor.movement_parameters[&muscle].muscle_length(), 30.0);
+
+        assert_eq!(editor.node_at(&Position::new(104.0, 100.0)), Some(a));
+        assert_eq!(editor.muscle_at(&Position::new(115.0, 102.0)), Some(muscle));
+        assert_eq!(editor.node_at(&Position::new(300.0, 300.0)), None);
+
+        editor.remove_node(b);
+
+        assert_eq!(editor.nodes.len(), 2);
+        assert!(editor.muscles.is_empty());
+        assert!(editor.movement_parameters.is_empty());
+
+        let creature = editor.build().unwrap();
+
+        assert_eq!(creature.nodes().len(), 2);
+        assert!(editor.start_test_run());
+    }
+}
@@ -0,0 +1,224 @@
+//! The animation between generations, showing who survives, which offspring they have and how the generation
+//! did compared to the last
+
+use eframe::egui;
+use egui::{Painter, Pos2, Stroke};
+
+use super::{paint_text, App, Emphasis, TEXT_COLOR};
+use crate::{
+    evolver::{CreatureSnapshot, Snapshot, STEPS_PER_EVOLUTION},
+    simulation::SimulationView,
+    statistics::GenerationStatistics,
+};
+
+const CULL_END: f32 = 0.3; // How far through evolving the culled creatures have faded away
+const BREED_END: f32 = 0.9; // How far through evolving every offspring has appeared
+const NURSERY_HEIGHT: f32 = 0.4; // How far down the screen the offspring are lined up
+const NURSERY_MARGIN: f32 = 40.0; // In screen pixels, either side of the offspring
+const OFFSPRING_RADIUS: f32 = 5.0;
+const LINK_THICKNESS: f32 = 1.0;
+const LINK_OPACITY: f32 = 0.5;
+const SUMMARY_TEXT_SIZE: f32 = 25.0;
+
+/// What's being shown while a generation is evolved
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Phase {
+    Culling,
+    Breeding,
+    Ready,
+}
+
+impl Phase {
+    /// Gets the phase at a fraction of the way through evolving
+    fn at(progress: f32) -> Phase {
+        if progress < CULL_END {
+            Phase::Culling
+        } else if progress < BREED_END {
+            Phase::Breeding
+        } else {
+            Phase::Ready
+        }
+    }
+
+    /// Describes what's happening in the phase
+    fn caption(&self) -> &'static str {
+        match self {
+            Phase::Culling => "The least fit creatures are culled",
+            Phase::Breeding => "The survivors have mutated offspring",
+            Phase::Ready => "The next generation is ready",
+        }
+    }
+}
+
+/// Gets how far through evolving a generation is, from 0 to 1
+fn progress(steps_left: i32) -> f32 {
+    (1.0 - steps_left as f32 / STEPS_PER_EVOLUTION as f32).clamp(0.0, 1.0)
+}
+
+/// The best and median score (distance) of a generation, and how they changed since the last one
+#[derive(Debug, PartialEq)]
+struct Summary {
+    best: f32,
+    median: f32,
+    best_change: Option<f32>,
+    median_change: Option<f32>,
+}
+
+impl Summary {
+    /// Summarizes the statistics of a generation, compared to the last one if there was one
+    fn new(statistics: &GenerationStatistics, last: Option<&GenerationStatistics>) -> Summary {
+        Summary {
+            best: statistics.best,
+            median: statistics.median,
+            best_change: last.map(|last| statistics.best - last.best),
+            median_change: last.map(|last| statistics.median - last.median),
+        }
+    }
+}
+
+/// Formats a distance along with how it changed, if known
+fn format_change(value: f32, change: Option<f32>) -> String {
+    match change {
+        Some(change) => format!("{:.2}m ({:+.2}m)", value, change),
+        None => format!("{:.2}m", value),
+    }
+}
+
+impl App {
+    /// Paints a generation as it's evolved. The culled creatures fade away, the survivors are highlighted and
+    /// their offspring appear one by one, linked to their parents
+    pub(super) fn paint_evolution(
+        &self,
+        snapshot: &Snapshot,
+        generation: &[CreatureSnapshot],
+        steps_left: i32,
+        painter: &Painter,
+    ) {
+        let progress = progress(steps_left);
+        let fitnesses: Vec<f32> = generation.iter().map(|view| view.get_fitness()).collect();
+        // Chosen just like the evolver chooses them
+        let selection = snapshot.settings().select(&fitnesses);
+        let fade = 1.0 - (progress / CULL_END).min(1.0);
+
+        if fade > 0.0 {
+            for (index, view) in generation.iter().enumerate() {
+                if !selection.survivors.contains(&index) {
+                    self.paint_faded_simulation(view, Emphasis::Normal, fade, painter);
+                }
+            }
+        }
+
+        for index in &selection.survivors {
+            self.paint_simulation(&generation[*index], Emphasis::Highlighted, painter);
+        }
+
+        // The next generation is lined up across the screen in the order it was made, each linked to its parent
+        let offspring = snapshot.offspring();
+        let screen_rect = self.camera.screen_rect();
+        let breeding = ((progress - CULL_END) / (BREED_END - CULL_END)).clamp(0.0, 1.0);
+        let born = (breeding * offspring.len() as f32).ceil() as usize;
+        let spacing = (screen_rect.width() - 2.0 * NURSERY_MARGIN) / offspring.len().max(1) as f32;
+        let y = screen_rect.min.y + screen_rect.height() * NURSERY_HEIGHT;
+
+        for (index, child) in offspring.iter().take(born).enumerate() {
+            let to = Pos2::new(
+                screen_rect.min.x + NURSERY_MARGIN + spacing * (index as f32 + 0.5),
+                y,
+            );
+            let parent = generation
+                .iter()
+                .find(|view| Some(view.creature().id()) == child.parent_id());
+
+            if let Some(parent) = parent {
+                let (top_left, bottom_right) = parent.get_bounds();
+                let from = self.camera.to_screen(&((top_left + bottom_right) / 2.0));
+                let color = parent.creature().colors().node();
+
+                painter.line_segment(
+                    [from, to],
+                    Stroke::new(LINK_THICKNESS, color.linear_multiply(LINK_OPACITY)),
+                );
+            }
+
+            painter.circle_filled(to, OFFSPRING_RADIUS, child.colors().node());
+        }
+    }
+
+    /// Paints what's happening while a generation is evolved and how it did, centered below a position
+    pub(super) fn paint_evolution_text(
+        &self,
+        snapshot: &Snapshot,
+        steps_left: i32,
+        mut position: Pos2,
+        painter: &Painter,
+    ) {
+        paint_text(
+            format!("Evolving generation {}...", snapshot.on_generation()),
+            position,
+            40.0,
+            TEXT_COLOR,
+            true,
+            painter,
+        );
+        position.y += 40.0;
+        paint_text(
+            Phase::at(progress(steps_left)).caption().to_owned(),
+            position,
+            30.0,
+            TEXT_COLOR,
+            true,
+            painter,
+        );
+        position.y += 30.0;
+
+        // The generation being evolved has already been summarized
+        let summary = match &*snapshot.generation_statistics() {
+            [.., last, statistics] => Summary::new(statistics, Some(last)),
+            [statistics] => Summary::new(statistics, None),
+            [] => return,
+        };
+
+        for (name, value, change) in [
+            ("Best", summary.best, summary.best_change),
+            ("Median", summary.median, summary.median_change),
+        ] {
+            paint_text(
+                format!("{} distance: {}", name, format_change(value, change)),
+                position,
+                SUMMARY_TEXT_SIZE,
+                TEXT_COLOR,
+                true,
+                painter,
+            );
+            position.y += SUMMARY_TEXT_SIZE;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    pub fn phases() {
+        assert_eq!(Phase::at(progress(STEPS_PER_EVOLUTION)), Phase::Culling);
+        assert_eq!(
+            Phase::at(progress(STEPS_PER_EVOLUTION / 2)),
+            Phase::Breeding
+        );
+        assert_eq!(Phase::at(progress(0)), Phase::Ready);
+
+        let last = GenerationStatistics::new(1, &[1.0, 2.0, 3.0], 0.0, 0.0, Duration::ZERO);
+        let statistics = GenerationStatistics::new(2, &[2.0, 6.0, 4.0], 0.0, 0.0, Duration::ZERO);
+        let summary = Summary::new(&statistics, Some(&last));
+
+        assert_eq!(summary.best_change, Some(3.0));
+        assert_eq!(summary.median_change, Some(2.0));
+        assert_eq!(
+            format_change(summary.best, summary.best_change),
+            "6.00m (+3.00m)"
+        );
+    }
+}
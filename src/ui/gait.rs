@@ -0,0 +1,256 @@
+//! Node trails and the gait diagram, which show how the selected creature has been moving
+
+use std::ops::Range;
+
+use eframe::egui;
+use egui::{Align2, Color32, FontId, Painter, Pos2, Rect, Rounding, Sense, Stroke, Vec2};
+use uuid::Uuid;
+
+use super::{App, TEXT_COLOR};
+use crate::{
+    evolver::{CreatureSnapshot, EvolverWorker, Snapshot, TrackedStep, TRACKED_STEPS},
+    simulation::{SimulationView, STEPS_PER_SECOND},
+};
+
+const TRAIL_STEPS: i32 = STEPS_PER_SECOND * 2; // How far back node trails go
+const TRAIL_THICKNESS: f32 = 2.0;
+const LABEL_WIDTH: f32 = 80.0;
+const ROW_HEIGHT: f32 = 14.0;
+const ROW_GAP: f32 = 2.0;
+const AXIS_HEIGHT: f32 = 16.0;
+const ROW_BACKGROUND_COLOR: Color32 = Color32::from_gray(40);
+const TICK_COLOR: Color32 = Color32::from_gray(90);
+const DIAGRAM_FONT_SIZE: f32 = 11.0;
+
+/// Whether the selected creature's trails and gait diagram are shown. Its movement is recorded by the worker
+#[derive(Default)]
+pub(crate) struct Gait {
+    show_trails: bool,
+    show_diagram: bool,
+    /// The creature the worker was last asked to track
+    tracking: Option<Uuid>,
+}
+
+impl Gait {
+    /// Returns true if the gait diagram is shown
+    pub(crate) fn is_diagram_shown(&self) -> bool {
+        self.show_diagram
+    }
+
+    /// Asks the worker to track the selected creature, if it isn't already
+    pub(crate) fn track(&mut self, worker: &EvolverWorker, selected: Option<Uuid>) {
+        if self.tracking != selected {
+            worker.track(selected);
+            self.tracking = selected;
+        }
+    }
+
+    /// Forgets what was tracked, for a new worker that isn't tracking anything yet
+    pub(crate) fn restart(&mut self) {
+        self.tracking = None;
+    }
+}
+
+/// Merges when something was on, sampled at increasing steps, into ranges of steps. Each sample lasts until
+/// the next one, and the last for one step
+fn spans(samples: &[(i32, bool)]) -> Vec<Range<i32>> {
+    let mut spans: Vec<Range<i32>> = Vec::new();
+
+    for (index, (step, on)) in samples.iter().enumerate() {
+        if !on {
+            continue;
+        }
+
+        let end = samples.get(index + 1).map_or(step + 1, |(next, _)| *next);
+
+        match spans.last_mut() {
+            Some(span) if span.end == *step => span.end = end,
+            _ => spans.push(*step..end),
+        }
+    }
+
+    spans
+}
+
+impl App {
+    /// Renders the checkboxes for the selected creature's trails and gait diagram
+    pub(super) fn render_gait_options(&mut self, ui: &mut egui::Ui) {
+        ui.label("Selected creature");
+        ui.checkbox(&mut self.gait.show_trails, "Node trails");
+        ui.checkbox(&mut self.gait.show_diagram, "Gait diagram")
+            .on_hover_text("When each node touches the ground and each muscle extends");
+    }
+
+    /// Paints fading trails behind each node of the selected creature, if they're shown
+    pub(super) fn paint_trails(
+        &self,
+        snapshot: &Snapshot,
+        selected: &CreatureSnapshot,
+        painter: &Painter,
+    ) {
+        let track = match snapshot.track() {
+            Some(track)
+                if self.gait.show_trails && track.creature() == *selected.creature().id() =>
+            {
+                track
+            }
+            _ => return,
+        };
+        let latest = match track.steps().back() {
+            Some(tracked) => tracked.step(),
+            None => return,
+        };
+        let recent: Vec<&TrackedStep> = track
+            .steps()
+            .iter()
+            .filter(|tracked| tracked.step() >= latest - TRAIL_STEPS)
+            .collect();
+        let color = selected.creature().colors().node();
+
+        for pair in recent.windows(2) {
+            let age = (latest - pair[1].step()) as f32 / TRAIL_STEPS as f32;
+            let stroke = Stroke::new(TRAIL_THICKNESS, color.linear_multiply(1.0 - age));
+
+            for (from, to) in pair[0].positions().iter().zip(pair[1].positions()) {
+                painter.line_segment(
+                    [self.camera.to_screen(from), self.camera.to_screen(to)],
+                    stroke,
+                );
+            }
+        }
+    }
+
+    /// Renders when each node of the selected creature touched the ground and each muscle was extending, over
+    /// the last few seconds
+    pub(super) fn render_gait_diagram(&self, ui: &mut egui::Ui) {
+        ui.heading("Gait diagram");
+
+        let generation = self
+            .snapshot
+            .as_ref()
+            .map(Snapshot::current_generation)
+            .unwrap_or_default();
+        let track = self
+            .snapshot
+            .as_ref()
+            .and_then(Snapshot::track)
+            .filter(|track| Some(track.creature()) == self.selected_creature);
+        let selected = track.and_then(|track| {
+            generation
+                .iter()
+                .find(|view| *view.creature().id() == track.creature())
+        });
+        let latest = track
+            .and_then(|track| track.steps().back())
+            .map(TrackedStep::step);
+        let (track, latest, colors) = match (track, latest, selected) {
+            (Some(track), Some(latest), Some(selected)) => {
+                (track, latest, selected.creature().colors())
+            }
+            _ => {
+                ui.label(
+                    "Select a creature to see when its nodes touch the ground and muscles extend",
+                );
+                return;
+            }
+        };
+        let node_color = colors.node();
+        let muscle_color = colors.muscle_extended();
+        let first = latest - TRACKED_STEPS;
+        let rows = track.node_ids().len() + track.muscle_ids().len();
+        let size = Vec2::new(ui.available_width(), rows as f32 * ROW_HEIGHT + AXIS_HEIGHT);
+        let (response, painter) = ui.allocate_painter(size, Sense::hover());
+        let rect = response.rect;
+        let plot_left = rect.min.x + LABEL_WIDTH;
+        let to_x = |step: i32| {
+            plot_left + (step - first) as f32 / TRACKED_STEPS as f32 * (rect.max.x - plot_left)
+        };
+        let font = FontId::monospace(DIAGRAM_FONT_SIZE);
+
+        // A tick every second, labeled with the time into the generation
+        for second in (first.max(0) / STEPS_PER_SECOND)..=(latest / STEPS_PER_SECOND) {
+            let x = to_x(second * STEPS_PER_SECOND);
+
+            if x < plot_left {
+                continue;
+            }
+
+            painter.line_segment(
+                [
+                    Pos2::new(x, rect.min.y),
+                    Pos2::new(x, rect.max.y - AXIS_HEIGHT),
+                ],
+                Stroke::new(1.0, TICK_COLOR),
+            );
+            painter.text(
+                Pos2::new(x, rect.max.y),
+                Align2::CENTER_BOTTOM,
+                format!("{}s", second),
+                font.clone(),
+                TEXT_COLOR,
+            );
+        }
+
+        let node_rows = (0..track.node_ids().len()).map(|node| {
+            let samples: Vec<(i32, bool)> = track
+                .steps()
+                .iter()
+                .map(|tracked| (tracked.step(), tracked.touching()[node]))
+                .collect();
+
+            (format!("Node {}", node + 1), node_color, samples)
+        });
+        let muscle_rows = (0..track.muscle_ids().len()).map(|muscle| {
+            let samples: Vec<(i32, bool)> = track
+                .steps()
+                .iter()
+                .map(|tracked| (tracked.step(), tracked.extending()[muscle]))
+                .collect();
+
+            (format!("Muscle {}", muscle + 1), muscle_color, samples)
+        });
+
+        for (row, (label, color, samples)) in node_rows.chain(muscle_rows).enumerate() {
+            let top = rect.min.y + row as f32 * ROW_HEIGHT;
+            let bottom = top + ROW_HEIGHT - ROW_GAP;
+
+            painter.text(
+                Pos2::new(rect.min.x, (top + bottom) / 2.0),
+                Align2::LEFT_CENTER,
+                label,
+                font.clone(),
+                TEXT_COLOR,
+            );
+            painter.rect_filled(
+                Rect::from_min_max(Pos2::new(plot_left, top), Pos2::new(rect.max.x, bottom)),
+                Rounding::none(),
+                ROW_BACKGROUND_COLOR,
+            );
+
+            for span in spans(&samples) {
+                painter.rect_filled(
+                    Rect::from_min_max(
+                        Pos2::new(to_x(span.start.max(first)), top),
+                        Pos2::new(to_x(span.end), bottom),
+                    ),
+                    Rounding::none(),
+                    color,
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    pub fn gait_spans() {
+        assert_eq!(
+            spans(&[(0, true), (2, true), (3, false), (5, true)]),
+            vec![0..3, 5..6]
+        );
+        assert!(spans(&[(0, false), (1, false)]).is_empty());
+    }
+}
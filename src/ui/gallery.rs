@@ -0,0 +1,372 @@
+//! The hall of fame gallery, where the best creatures ever evolved can be browsed, replayed and reused
+
+use std::{
+    fs, mem,
+    path::Path,
+    slice,
+    sync::mpsc::{self, Receiver},
+    thread,
+    time::{Duration, Instant},
+};
+
+use eframe::egui;
+use egui::{RichText, ScrollArea};
+
+use super::{App, AppState, Emphasis, TEXT_COLOR};
+use crate::{
+    ancestry::short_id,
+    export::{self, Rasterizer, DEFAULT_IMAGE_HEIGHT, DEFAULT_IMAGE_WIDTH},
+    hall_of_fame::{HallOfFame, HallOfFameEntry, HALL_OF_FAME_DIRECTORY},
+    simulation::{Fitness, Simulation, SimulationView, Terrain, STEPS_FREQUENCY},
+};
+
+const PANEL_WIDTH: f32 = 360.0;
+const LIST_HEIGHT: f32 = 300.0;
+
+/// Which entries of the [HallOfFame] are listed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum Listing {
+    #[default]
+    AllTime,
+    GenerationBests,
+}
+
+/// The state of the gallery screen
+#[derive(Default)]
+pub(crate) struct Gallery {
+    hall_of_fame: HallOfFame,
+    listing: Listing,
+    /// The index of the selected entry in the current listing
+    selected: Option<usize>,
+    /// A fresh simulation of the selected entry, which runs for as long as it was evaluated
+    replay: Option<Simulation>,
+    steps_left: i32,
+    time_left_over: Duration,
+    message: Option<String>,
+    /// Reports back once a GIF has been exported
+    export: Option<Receiver<String>>,
+}
+
+impl Gallery {
+    /// Opens the gallery on the hall of fame kept at its default path
+    pub(crate) fn open() -> Gallery {
+        match HallOfFame::load(&HallOfFame::default_path()) {
+            Ok(hall_of_fame) => Gallery {
+                hall_of_fame,
+                ..Default::default()
+            },
+            Err(error) => Gallery {
+                message: Some(format!("Failed to load the hall of fame: {}", error)),
+                ..Default::default()
+            },
+        }
+    }
+
+    /// Gets the entries of the current listing
+    fn entries(&self) -> &[HallOfFameEntry] {
+        match self.listing {
+            Listing::AllTime => self.hall_of_fame.all_time(),
+            Listing::GenerationBests => self.hall_of_fame.generation_bests(),
+        }
+    }
+
+    /// Gets the selected entry, if any
+    fn selected_entry(&self) -> Option<&HallOfFameEntry> {
+        self.selected.and_then(|index| self.entries().get(index))
+    }
+
+    /// Selects an entry and starts replaying it from the beginning
+    fn select(&mut self, index: usize) {
+        self.selected = Some(index);
+        self.replay = None;
+
+        if let Some(entry) = self.selected_entry() {
+            let simulation =
+                Simulation::with_environment(entry.creature.clone(), entry.settings.environment);
+            let steps = entry.settings.steps_per_generation();
+
+            self.replay = Some(simulation);
+            self.steps_left = steps;
+            self.time_left_over = Duration::ZERO;
+        }
+    }
+
+    /// Runs the replay for a certain amount of time, stopping once it's been evaluated for as long as it was
+    fn run(&mut self, mut time: Duration) {
+        let simulation = match &mut self.replay {
+            Some(simulation) => simulation,
+            None => return,
+        };
+
+        time += self.time_left_over;
+
+        while time > STEPS_FREQUENCY && self.steps_left > 0 {
+            time -= STEPS_FREQUENCY;
+            self.steps_left -= 1;
+            simulation.step();
+        }
+
+        self.time_left_over = time;
+    }
+
+    /// Exports a GIF of the selected entry in the background, into [HALL_OF_FAME_DIRECTORY]
+    fn export_gif(&mut self) {
+        let entry = match self.selected_entry() {
+            Some(entry) => entry,
+            None => return,
+        };
+
+        let creature = entry.creature.clone();
+        let environment = entry.settings.environment;
+        let path =
+            Path::new(HALL_OF_FAME_DIRECTORY).join(format!("{}.gif", short_id(creature.id())));
+        let (sender, receiver) = mpsc::channel();
+
+        thread::spawn(move || {
+            let rasterizer = Rasterizer::new(DEFAULT_IMAGE_WIDTH, DEFAULT_IMAGE_HEIGHT);
+            let result = fs::create_dir_all(HALL_OF_FAME_DIRECTORY).and_then(|_| {
+                export::export_gif(
+                    &export::record_creature(creature, environment),
+                    &rasterizer,
+                    &path,
+                )
+            });
+            let message = match result {
+                Ok(_) => format!("Exported {}", path.display()),
+                Err(error) => format!("Failed to export: {}", error),
+            };
+
+            let _ = sender.send(message);
+        });
+
+        self.export = Some(receiver);
+        self.message = Some("Exporting...".to_owned());
+    }
+}
+
+impl App {
+    /// Renders the hall of fame gallery
+    pub(super) fn render_gallery(&mut self, ui: &mut egui::Ui) {
+        let now = Instant::now();
+        let mut gallery = mem::take(&mut self.gallery);
+
+        if let Some(last_frame) = self.last_frame {
+            gallery.run(now.duration_since(last_frame));
+        }
+
+        self.last_frame = Some(now);
+
+        if let Some(message) = gallery
+            .export
+            .as_ref()
+            .and_then(|export| export.try_recv().ok())
+        {
+            gallery.message = Some(message);
+            gallery.export = None;
+        }
+
+        // Paint before the panel so the panel is drawn on top
+        match &gallery.replay {
+            Some(simulation) => {
+                self.camera
+                    .follow(slice::from_ref(simulation), Some(simulation));
+                self.paint_scenery(simulation.terrain(), ui.painter());
+                self.paint_simulation(simulation, Emphasis::Normal, ui.painter());
+            }
+            None => {
+                self.camera.reset();
+                self.paint_scenery(Terrain::Flat, ui.painter());
+            }
+        }
+
+        egui::SidePanel::right("Gallery panel")
+            .resizable(false)
+            .default_width(PANEL_WIDTH)
+            .show_inside(ui, |ui| self.render_gallery_panel(ui, &mut gallery));
+
+        self.gallery = gallery;
+    }
+
+    /// Renders the list of entries, the selected entry's details and what can be done with it
+    fn render_gallery_panel(&mut self, ui: &mut egui::Ui, gallery: &mut Gallery) {
+        ui.heading("Hall of fame");
+        ui.add_space(5.0);
+
+        ui.horizontal(|ui| {
+            let mut listing = gallery.listing;
+
+            ui.selectable_value(&mut listing, Listing::AllTime, "All time");
+            ui.selectable_value(&mut listing, Listing::GenerationBests, "Generation bests");
+
+            if listing != gallery.listing {
+                gallery.listing = listing;
+                gallery.selected = None;
+                gallery.replay = None;
+            }
+        });
+
+        if gallery.entries().is_empty() {
+            ui.label("Nothing yet. The best creature of every generation is entered as runs go on");
+        }
+
+        let mut clicked = None;
+
+        ScrollArea::vertical()
+            .max_height(LIST_HEIGHT)
+            .show(ui, |ui| {
+                let entries = gallery.entries();
+                // Generation bests are newest first, so the current run is at the top
+                let order: Vec<usize> = match gallery.listing {
+                    Listing::AllTime => (0..entries.len()).collect(),
+                    Listing::GenerationBests => (0..entries.len()).rev().collect(),
+                };
+
+                for index in order {
+                    let entry = &entries[index];
+                    let text = match gallery.listing {
+                        Listing::AllTime => format!(
+                            "{:>2}. {:>8.2}m  generation {}",
+                            index + 1,
+                            entry.score,
+                            entry.generation
+                        ),
+                        Listing::GenerationBests => format!(
+                            "Run {} generation {}: {:.2}m",
+                            short_id(&entry.run_id),
+                            entry.generation,
+                            entry.score
+                        ),
+                    };
+
+                    if ui
+                        .selectable_label(
+                            gallery.selected == Some(index),
+                            RichText::new(text).monospace(),
+                        )
+                        .clicked()
+                    {
+                        clicked = Some(index);
+                    }
+                }
+            });
+
+        if let Some(index) = clicked {
+            gallery.select(index);
+        }
+
+        ui.separator();
+
+        if let Some(entry) = gallery.selected_entry() {
+            let settings = &entry.settings;
+
+            egui::Grid::new("Gallery entry")
+                .num_columns(2)
+                .show(ui, |ui| {
+                    ui.label("Score");
+                    ui.label(format!("{:.2}m", entry.score));
+                    ui.end_row();
+
+                    if settings.fitness != Fitness::Distance {
+                        ui.label(settings.fitness.name());
+                        ui.label(format!("{:.2}", entry.fitness));
+                        ui.end_row();
+                    }
+
+                    ui.label("Generation");
+                    ui.label(entry.generation.to_string());
+                    ui.end_row();
+
+                    ui.label("Run");
+                    ui.label(short_id(&entry.run_id));
+                    ui.end_row();
+
+                    ui.label("Seed");
+                    ui.label(entry.seed.to_string());
+                    ui.end_row();
+
+                    ui.label("Population");
+                    ui.label(settings.population.to_string());
+                    ui.end_row();
+
+                    ui.label("Evaluation time");
+                    ui.label(format!("{}s", settings.evaluation_seconds));
+                    ui.end_row();
+
+                    ui.label("Terrain");
+                    ui.label(settings.environment.terrain.name());
+                    ui.end_row();
+
+                    ui.label("Gravity");
+                    ui.label(settings.environment.gravity.to_string());
+                    ui.end_row();
+
+                    ui.label("Nodes");
+                    ui.label(entry.creature.nodes().len().to_string());
+                    ui.end_row();
+
+                    ui.label("Muscles");
+                    ui.label(entry.creature.muscles().len().to_string());
+                    ui.end_row();
+                });
+
+            if let Some(simulation) = &gallery.replay {
+                let status = if gallery.steps_left > 0 {
+                    format!("Replaying: {:.2}m", simulation.get_score())
+                } else {
+                    format!("Finished: {:.2}m", simulation.get_score())
+                };
+
+                ui.label(status);
+            }
+
+            let creature = entry.creature.clone();
+
+            ui.horizontal_wrapped(|ui| {
+                if ui.button("Replay").clicked() {
+                    if let Some(index) = gallery.selected {
+                        gallery.select(index);
+                    }
+                }
+
+                if ui.button("Save creature").clicked() {
+                    let path = creature.default_path();
+
+                    gallery.message = Some(match creature.save(&path) {
+                        Ok(_) => format!("Saved to {}", path.display()),
+                        Err(error) => format!("Failed to save: {}", error),
+                    });
+                }
+
+                if ui
+                    .add_enabled(gallery.export.is_none(), egui::Button::new("Export GIF"))
+                    .clicked()
+                {
+                    gallery.export_gif();
+                }
+
+                if ui.button("Evolve from this creature").clicked() {
+                    // Uses the last run's settings, which can be changed in the run setup
+                    match self.setup.start_from(vec![creature.clone()]) {
+                        Ok(worker) => {
+                            self.start_run(worker);
+                            gallery.replay = None;
+                        }
+                        Err(message) => gallery.message = Some(message),
+                    }
+                }
+            });
+        }
+
+        if let Some(message) = &gallery.message {
+            ui.label(RichText::new(message).color(TEXT_COLOR));
+        }
+
+        ui.add_space(10.0);
+
+        if ui.button("Return to menu").clicked() {
+            self.state = AppState::MainMenu;
+            self.last_frame = None;
+            gallery.replay = None;
+        }
+    }
+}
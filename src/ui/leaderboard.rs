@@ -0,0 +1,61 @@
+//! The leaderboard, a live ranking of the current generation by score
+
+use eframe::egui;
+use egui::{RichText, Rounding, ScrollArea, Sense, Vec2};
+
+use super::{App, FollowMode};
+use crate::{ancestry::short_id, simulation::SimulationView, util};
+
+const SWATCH_SIZE: Vec2 = Vec2::new(12.0, 12.0);
+
+impl App {
+    /// Renders every creature of the current generation from furthest to least far. Clicking a row selects
+    /// that creature and follows it
+    pub(super) fn render_leaderboard(&mut self, ui: &mut egui::Ui) {
+        ui.heading("Leaderboard");
+        ui.add_space(5.0);
+
+        let snapshot = match &self.snapshot {
+            Some(snapshot) => snapshot,
+            None => return,
+        };
+
+        let mut generation = snapshot.current_generation();
+        generation.sort_by(|a, b| util::cmp_f32(&b.get_score(), &a.get_score()));
+
+        let mut clicked = None;
+
+        ScrollArea::vertical().show(ui, |ui| {
+            for (index, view) in generation.iter().enumerate() {
+                let creature = view.creature();
+                let parent = match creature.parent_id() {
+                    Some(parent_id) => short_id(parent_id),
+                    None => "random".to_owned(),
+                };
+
+                ui.horizontal(|ui| {
+                    ui.label(RichText::new(format!("{:>3}", index + 1)).monospace());
+
+                    let (swatch, _) = ui.allocate_exact_size(SWATCH_SIZE, Sense::hover());
+                    ui.painter()
+                        .rect_filled(swatch, Rounding::same(2.0), creature.colors().node());
+
+                    let text = format!("{:>8.2}m  from {}", view.get_score(), parent);
+                    let is_selected = self.selected_creature == Some(*creature.id());
+
+                    if ui
+                        .selectable_label(is_selected, RichText::new(text).monospace())
+                        .clicked()
+                    {
+                        clicked = Some(*creature.id());
+                    }
+                });
+            }
+        });
+
+        if let Some(id) = clicked {
+            self.selected_creature = Some(id);
+            self.camera.set_follow_mode(FollowMode::Selected);
+        }
+    }
+}
@@ -0,0 +1,320 @@
+//! The run setup screen, where the settings of a new run are chosen before it starts
+
+use std::path::{Path, PathBuf};
+
+use eframe::egui;
+use egui::{Align, Color32, DragValue, FontId, Grid, Layout, RichText, Slider};
+use serde::{Deserialize, Serialize};
+
+use super::{App, AppState, TEXT_COLOR};
+use crate::{
+    creature::{Creature, CREATURES_DIRECTORY},
+    evolver::{
+        Evolver, EvolverSettings, EvolverWorker, EVALUATION_SECONDS_RANGE, GRAVITY_RANGE,
+        OFFSPRING_PER_SURVIVOR_RANGE, POPULATION_RANGE,
+    },
+    hall_of_fame::HallOfFame,
+    simulation::{Fitness, Terrain},
+};
+
+const STORAGE_KEY: &str = "run_setup"; // Where the last used setup is kept in eframe's storage
+const ERROR_COLOR: Color32 = Color32::from_rgb(200, 40, 40);
+
+/// The settings of the next run, which are remembered between sessions
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+pub(crate) struct Setup {
+    settings: EvolverSettings,
+    /// Whether the first generation is made from the creatures in [CREATURES_DIRECTORY]
+    from_saved_creatures: bool,
+    #[serde(skip)]
+    message: Option<String>,
+}
+
+impl Setup {
+    /// Loads the last used setup, or the defaults if there isn't one
+    pub(crate) fn load(storage: Option<&dyn eframe::Storage>) -> Setup {
+        storage
+            .and_then(|storage| eframe::get_value(storage, STORAGE_KEY))
+            .unwrap_or_default()
+    }
+
+    /// Remembers the setup for next time
+    pub(crate) fn save(&self, storage: &mut dyn eframe::Storage) {
+        eframe::set_value(storage, STORAGE_KEY, self);
+    }
+
+    /// Gets the settings of the next run
+    pub(crate) fn settings(&self) -> &EvolverSettings {
+        &self.settings
+    }
+
+    /// Starts running an [Evolver] with the settings, describing what's wrong if it can't
+    pub(crate) fn start(&self) -> Result<EvolverWorker, String> {
+        let templates = if self.from_saved_creatures {
+            load_saved_creatures()?
+        } else {
+            Vec::new()
+        };
+
+        self.start_from(templates)
+    }
+
+    /// Starts running an [Evolver] with the settings and a first generation made from template creatures,
+    /// describing what's wrong if it can't
+    pub(crate) fn start_from(&self, templates: Vec<Creature>) -> Result<EvolverWorker, String> {
+        self.start_with_hall_of_fame(templates, HallOfFame::default_path())
+    }
+
+    /// Starts running an [Evolver] like [Setup::start_from], keeping the hall of fame at a path
+    fn start_with_hall_of_fame(
+        &self,
+        templates: Vec<Creature>,
+        hall_of_fame_path: PathBuf,
+    ) -> Result<EvolverWorker, String> {
+        self.settings.validate()?;
+
+        Ok(spawn_evolver(
+            self.settings.clone(),
+            templates,
+            hall_of_fame_path,
+        ))
+    }
+}
+
+/// Starts running an [Evolver] that enters its best creatures into the [HallOfFame] at a path
+pub(crate) fn spawn_evolver(
+    settings: EvolverSettings,
+    templates: Vec<Creature>,
+    hall_of_fame_path: PathBuf,
+) -> EvolverWorker {
+    EvolverWorker::spawn(move || {
+        let evolver = Evolver::with_settings(settings, templates);
+
+        match HallOfFame::load(&hall_of_fame_path) {
+            Ok(hall_of_fame) => evolver.with_hall_of_fame(hall_of_fame),
+            Err(error) => {
+                eprintln!(
+                    "Failed to load the hall of fame, so it won't be kept: {}",
+                    error
+                );
+                evolver
+            }
+        }
+    })
+}
+
+/// Loads every creature in [CREATURES_DIRECTORY], failing if there aren't any
+pub(crate) fn load_saved_creatures() -> Result<Vec<Creature>, String> {
+    match Creature::load_directory(Path::new(CREATURES_DIRECTORY)) {
+        Ok(creatures) if !creatures.is_empty() => Ok(creatures),
+        Ok(_) => Err(format!("No creatures saved in {}", CREATURES_DIRECTORY)),
+        Err(error) => Err(format!("Failed to load creatures: {}", error)),
+    }
+}
+
+impl App {
+    /// Renders the run setup screen
+    pub(super) fn render_setup(&mut self, ui: &mut egui::Ui) {
+        self.camera.reset();
+        self.paint_scenery(Terrain::Flat, ui.painter());
+
+        ui.with_layout(Layout::top_down(Align::Center), |ui| {
+            ui.add_space(20.0);
+            ui.label(
+                RichText::new("Run setup")
+                    .font(FontId::proportional(40.0))
+                    .color(TEXT_COLOR),
+            );
+            ui.add_space(10.0);
+
+            egui::containers::Frame::popup(ui.style()).show(ui, |ui| {
+                edit_settings(ui, &mut self.setup);
+            });
+
+            ui.add_space(10.0);
+
+            let validation = self.setup.settings.validate();
+
+            if let Err(problem) = &validation {
+                ui.label(RichText::new(problem).color(ERROR_COLOR));
+            }
+
+            ui.horizontal(|ui| {
+                if ui
+                    .add_enabled(
+                        validation.is_ok(),
+                        egui::Button::new(RichText::new("Start").font(FontId::proportional(25.0))),
+                    )
+                    .clicked()
+                {
+                    match self.setup.start() {
+                        Ok(worker) => {
+                            self.start_run(worker);
+                            self.setup.message = None;
+                        }
+                        Err(message) => self.setup.message = Some(message),
+                    }
+                }
+
+                if ui.button("Reset to defaults").clicked() {
+                    self.setup = Setup::default();
+                }
+
+                if ui.button("Back").clicked() {
+                    self.state = AppState::MainMenu;
+                    self.setup.message = None;
+                }
+            });
+
+            if let Some(message) = &self.setup.message {
+                ui.label(RichText::new(message).color(ERROR_COLOR));
+            }
+        });
+    }
+}
+
+/// Renders the controls for every setting
+fn edit_settings(ui: &mut egui::Ui, setup: &mut Setup) {
+    let settings = &mut setup.settings;
+
+    Grid::new("Run settings")
+        .num_columns(2)
+        .spacing([20.0, 8.0])
+        .show(ui, |ui| {
+            ui.label("Population");
+            ui.add(
+                DragValue::new(&mut settings.population)
+                    .clamp_range(POPULATION_RANGE)
+                    .suffix(" creatures"),
+            );
+            ui.end_row();
+
+            ui.label("Evaluation time");
+            ui.add(
+                DragValue::new(&mut settings.evaluation_seconds)
+                    .clamp_range(EVALUATION_SECONDS_RANGE)
+                    .speed(0.1)
+                    .suffix("s"),
+            );
+            ui.end_row();
+
+            ui.label("Selected by");
+            ui.horizontal(|ui| {
+                for fitness in Fitness::ALL {
+                    ui.selectable_value(&mut settings.fitness, fitness, fitness.name());
+                }
+            });
+            ui.end_row();
+
+            ui.label("Offspring per survivor");
+            ui.horizontal(|ui| {
+                ui.add(
+                    DragValue::new(&mut settings.offspring_per_survivor)
+                        .clamp_range(OFFSPRING_PER_SURVIVOR_RANGE),
+                );
+                ui.label(format!("(the best {} survive)", settings.survivors()));
+            });
+            ui.end_row();
+
+            ui.label("Muscle mutation chance");
+            ui.add(Slider::new(&mut settings.mutation.muscle_chance, 0.0..=1.0));
+            ui.end_row();
+
+            ui.label("Controller switch chance");
+            ui.add(Slider::new(
+                &mut settings.mutation.controller_switch_chance,
+                0.0..=1.0,
+            ));
+            ui.end_row();
+
+            ui.label("Gravity");
+            ui.add(
+                DragValue::new(&mut settings.environment.gravity)
+                    .clamp_range(GRAVITY_RANGE)
+                    .speed(1.0),
+            );
+            ui.end_row();
+
+            ui.label("Terrain");
+            ui.horizontal(|ui| {
+                for terrain in Terrain::ALL {
+                    ui.selectable_value(&mut settings.environment.terrain, terrain, terrain.name());
+                }
+            });
+            ui.end_row();
+
+            ui.label("Random seed");
+            ui.horizontal(|ui| {
+                let mut fixed = settings.seed.is_some();
+
+                ui.checkbox(&mut fixed, "Fixed");
+
+                if fixed {
+                    ui.add(DragValue::new(settings.seed.get_or_insert(0)));
+                } else {
+                    settings.seed = None;
+                }
+            });
+            ui.end_row();
+
+            ui.label("Seed creatures");
+            ui.checkbox(
+                &mut setup.from_saved_creatures,
+                format!("Start from the creatures in {}", CREATURES_DIRECTORY),
+            );
+            ui.end_row();
+
+            ui.label("Random immigrants").on_hover_text(
+                "How many random creatures join seed creatures or a design in the first generation",
+            );
+            ui.add(
+                DragValue::new(&mut settings.random_immigrants)
+                    .clamp_range(0..=settings.population),
+            );
+            ui.end_row();
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::util::TestDirectory;
+
+    #[test]
+    pub fn setup() {
+        let directory = TestDirectory::new("setup");
+        let hall_of_fame_path = directory.path().join("hall_of_fame.json");
+        let mut setup = Setup::default();
+
+        setup.settings.population = 1;
+
+        assert!(setup
+            .start_with_hall_of_fame(Vec::new(), hall_of_fame_path.clone())
+            .is_err());
+
+        setup.settings.population = 4;
+        setup.settings.evaluation_seconds = 1.0;
+        setup.settings.seed = Some(1);
+
+        let worker = setup
+            .start_with_hall_of_fame(Vec::new(), hall_of_fame_path.clone())
+            .unwrap();
+        let snapshot = worker.wait_for_snapshot(Duration::from_secs(10)).unwrap();
+
+        assert_eq!(snapshot.current_generation().len(), 4);
+        assert_eq!(snapshot.settings(), &setup.settings);
+
+        // The best of the finished generation is saved once the worker stops
+        worker.set_paused(true);
+        worker.finish_generation();
+        drop(worker);
+
+        assert!(!HallOfFame::load(&hall_of_fame_path)
+            .unwrap()
+            .generation_bests()
+            .is_empty());
+    }
+}
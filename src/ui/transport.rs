@@ -0,0 +1,256 @@
+//! The transport bar, which pauses, steps, sets the speed of the simulation and switches turbo on and off
+
+use std::ops::RangeInclusive;
+
+use eframe::egui;
+use egui::{DragValue, FontId, Key, RichText};
+
+use super::{App, TEXT_COLOR};
+
+const SPEED_PRESETS: [f32; 10] = [0.25, 0.5, 0.75, 1.0, 1.5, 2.0, 3.0, 5.0, 7.5, 10.0];
+const SPEED_RANGE: RangeInclusive<f32> = 0.01..=20.0;
+const DEFAULT_SPEED: f32 = 1.0;
+const PAUSE_KEY: Key = Key::Space;
+const STEP_FRAME_KEY: Key = Key::N;
+const STEP_GENERATION_KEY: Key = Key::G;
+const FASTER_KEY: Key = Key::ArrowUp;
+const SLOWER_KEY: Key = Key::ArrowDown;
+const TURBO_KEY: Key = Key::T;
+
+/// How fast the simulation runs, and whether it's running at all
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Transport {
+    speed: f32,
+    paused: bool,
+    /// Whether evolution runs as fast as it can without drawing creatures, ignoring the speed
+    turbo: bool,
+}
+
+impl Transport {
+    /// Gets the speed multiplier, which is kept while paused
+    pub(crate) fn speed(&self) -> f32 {
+        self.speed
+    }
+
+    /// Sets the speed multiplier, clamped to the allowed range
+    pub(crate) fn set_speed(&mut self, speed: f32) {
+        self.speed = speed.clamp(*SPEED_RANGE.start(), *SPEED_RANGE.end());
+    }
+
+    /// Returns true if the simulation is paused
+    pub(crate) fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Pauses or resumes the simulation
+    pub(crate) fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+    }
+
+    /// Returns true if in turbo
+    pub(crate) fn is_turbo(&self) -> bool {
+        self.turbo
+    }
+
+    /// Turns turbo on or off
+    pub(crate) fn set_turbo(&mut self, turbo: bool) {
+        self.turbo = turbo;
+    }
+
+    /// Goes up to the next preset speed
+    pub(crate) fn faster(&mut self) {
+        if let Some(speed) = SPEED_PRESETS.iter().find(|speed| **speed > self.speed) {
+            self.speed = *speed;
+        }
+    }
+
+    /// Goes down to the previous preset speed
+    pub(crate) fn slower(&mut self) {
+        if let Some(speed) = SPEED_PRESETS
+            .iter()
+            .rev()
+            .find(|speed| **speed < self.speed)
+        {
+            self.speed = *speed;
+        }
+    }
+}
+
+impl Default for Transport {
+    fn default() -> Self {
+        Transport {
+            speed: DEFAULT_SPEED,
+            paused: false,
+            turbo: false,
+        }
+    }
+}
+
+/// Something the transport bar asked for, either by a button or a shortcut
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TransportAction {
+    TogglePaused,
+    StepFrame,
+    StepGeneration,
+    Faster,
+    Slower,
+    ToggleTurbo,
+}
+
+impl App {
+    /// Renders the pause, step, speed and turbo controls, and handles their keyboard shortcuts
+    pub(super) fn render_transport_bar(&mut self, ui: &mut egui::Ui) {
+        let mut actions = Vec::new();
+
+        // Shortcuts are ignored while a widget has focus, like while typing into the speed field or when space
+        // would press a focused button
+        let focused = ui.memory().focus().is_some();
+
+        if !focused {
+            let input = ui.input();
+
+            for (key, action) in [
+                (PAUSE_KEY, TransportAction::TogglePaused),
+                (STEP_FRAME_KEY, TransportAction::StepFrame),
+                (STEP_GENERATION_KEY, TransportAction::StepGeneration),
+                (FASTER_KEY, TransportAction::Faster),
+                (SLOWER_KEY, TransportAction::Slower),
+                (TURBO_KEY, TransportAction::ToggleTurbo),
+            ] {
+                if input.key_pressed(key) {
+                    actions.push(action);
+                }
+            }
+        }
+
+        ui.horizontal_top(|ui| {
+            let pause_text = if self.transport.is_paused() {
+                "Resume"
+            } else {
+                "Pause"
+            };
+
+            if ui.button(pause_text).on_hover_text("Space").clicked() {
+                actions.push(TransportAction::TogglePaused);
+            }
+
+            if ui
+                .button("Step frame")
+                .on_hover_text("N: runs one physics step, then pauses")
+                .clicked()
+            {
+                actions.push(TransportAction::StepFrame);
+            }
+
+            if ui
+                .button("Step generation")
+                .on_hover_text("G: runs until the next generation starts, then pauses")
+                .clicked()
+            {
+                actions.push(TransportAction::StepGeneration);
+            }
+
+            ui.add_space(10.0);
+
+            if ui.button("<").on_hover_text("Down arrow").clicked() {
+                actions.push(TransportAction::Slower);
+            }
+
+            ui.label(
+                RichText::new("Speed:")
+                    .font(FontId::proportional(25.0))
+                    .color(TEXT_COLOR),
+            );
+
+            let mut speed = self.transport.speed();
+
+            if ui
+                .add(
+                    DragValue::new(&mut speed)
+                        .speed(0.05)
+                        .clamp_range(SPEED_RANGE)
+                        .suffix("x"),
+                )
+                .changed()
+            {
+                self.transport.set_speed(speed);
+            }
+
+            if ui.button(">").on_hover_text("Up arrow").clicked() {
+                actions.push(TransportAction::Faster);
+            }
+
+            ui.add_space(10.0);
+
+            if ui
+                .selectable_label(self.transport.is_turbo(), "Turbo")
+                .on_hover_text("T: evolves as fast as possible without drawing creatures")
+                .clicked()
+            {
+                actions.push(TransportAction::ToggleTurbo);
+            }
+        });
+
+        let worker = match &self.worker {
+            Some(worker) => worker,
+            None => return,
+        };
+
+        for action in actions {
+            match action {
+                TransportAction::TogglePaused => {
+                    self.transport.set_paused(!self.transport.is_paused());
+                }
+                TransportAction::StepFrame => {
+                    self.transport.set_paused(true);
+                    worker.set_paused(true);
+                    worker.step();
+                }
+                TransportAction::StepGeneration => {
+                    self.transport.set_paused(true);
+                    worker.set_paused(true);
+                    worker.finish_generation();
+                }
+                TransportAction::Faster => self.transport.faster(),
+                TransportAction::Slower => self.transport.slower(),
+                TransportAction::ToggleTurbo => {
+                    self.transport.set_turbo(!self.transport.is_turbo());
+                }
+            }
+        }
+
+        worker.set_speed(self.transport.speed());
+        worker.set_paused(self.transport.is_paused());
+        worker.set_turbo(self.transport.is_turbo());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    pub fn transport() {
+        let mut transport = Transport::default();
+
+        transport.set_speed(0.3);
+        transport.slower();
+
+        assert_eq!(transport.speed(), 0.25);
+
+        // Stays put at the ends instead of running off the presets
+        transport.slower();
+
+        assert_eq!(transport.speed(), 0.25);
+
+        transport.set_speed(100.0);
+        transport.faster();
+
+        assert_eq!(transport.speed(), *SPEED_RANGE.end());
+
+        transport.set_speed(2.0);
+        transport.faster();
+
+        assert_eq!(transport.speed(), 3.0);
+    }
+}
@@ -0,0 +1,128 @@
+//! The turbo dashboard, shown in place of the creatures while evolution runs as fast as it can
+
+use eframe::egui;
+use egui::{Align, FontId, Layout, ProgressBar, RichText, Vec2};
+
+use super::{App, TEXT_COLOR};
+use crate::{
+    evolver::{EvolverState, Snapshot},
+    statistics::GenerationStatistics,
+};
+
+const RATE_WINDOW: usize = 5; // How many of the latest generations the rate is averaged over
+const CHART_SIZE: Vec2 = Vec2::new(800.0, 400.0);
+
+/// Gets how many generations were finished per second, averaged over the latest few
+fn generations_per_second(statistics: &[GenerationStatistics]) -> Option<f32> {
+    let recent = &statistics[statistics.len().saturating_sub(RATE_WINDOW)..];
+    let seconds: f32 = recent
+        .iter()
+        .map(|statistics| statistics.wall_time_seconds)
+        .sum();
+
+    if seconds > 0.0 {
+        Some(recent.len() as f32 / seconds)
+    } else {
+        None
+    }
+}
+
+/// Gets how far through the current generation the evolver is, from 0 to 1
+fn generation_progress(snapshot: &Snapshot) -> f32 {
+    match snapshot.state() {
+        EvolverState::SimulatingGeneration { steps_left } => {
+            let steps = snapshot.settings().steps_per_generation().max(1);
+
+            1.0 - steps_left as f32 / steps as f32
+        }
+        EvolverState::Evolving { steps_left: _ } => 1.0,
+    }
+}
+
+impl App {
+    /// Renders the progress of evolution while in turbo, without drawing any creatures
+    pub(super) fn render_turbo_dashboard(&mut self, ui: &mut egui::Ui, snapshot: &Snapshot) {
+        self.paint_scenery(snapshot.settings().environment.terrain, ui.painter());
+
+        egui::containers::Frame::none()
+            .inner_margin(10.0)
+            .show(ui, |ui| {
+                self.render_transport_bar(ui);
+
+                ui.with_layout(Layout::top_down(Align::Center), |ui| {
+                    let statistics = snapshot.generation_statistics();
+
+                    ui.label(
+                        RichText::new(format!("Generation {}", snapshot.on_generation()))
+                            .font(FontId::proportional(60.0))
+                            .color(TEXT_COLOR),
+                    );
+
+                    let rate = match generations_per_second(&statistics) {
+                        Some(rate) => format!("{:.2} generations/second", rate),
+                        None => "Measuring speed...".to_owned(),
+                    };
+
+                    ui.label(
+                        RichText::new(rate)
+                            .font(FontId::proportional(30.0))
+                            .color(TEXT_COLOR),
+                    );
+
+                    if let Some(last) = statistics.last() {
+                        ui.label(
+                            RichText::new(format!("Last generation's best: {:.2}m", last.best))
+                                .font(FontId::proportional(25.0))
+                                .color(TEXT_COLOR),
+                        );
+                    }
+
+                    ui.add(
+                        ProgressBar::new(generation_progress(snapshot))
+                            .desired_width(CHART_SIZE.x)
+                            .text("Current generation"),
+                    );
+
+                    ui.add_space(10.0);
+
+                    ui.checkbox(
+                        &mut self.show_percentile_band,
+                        RichText::new("Show 10th to 90th percentile").color(TEXT_COLOR),
+                    );
+
+                    Self::render_fitness_chart(
+                        ui,
+                        &statistics,
+                        self.show_percentile_band,
+                        CHART_SIZE,
+                    );
+
+                    if ui.button("Return to menu").clicked() {
+                        self.end_run();
+                    }
+                });
+            });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    pub fn rate() {
+        let generation = |wall_time: f32| {
+            GenerationStatistics::new(1, &[1.0], 3.0, 2.0, Duration::from_secs_f32(wall_time))
+        };
+
+        assert_eq!(generations_per_second(&[]), None);
+
+        // Only the latest generations count, so slow ones from before turbo are soon forgotten
+        let mut statistics = vec![generation(20.0); 3];
+        statistics.extend(vec![generation(0.5); RATE_WINDOW]);
+
+        assert_eq!(generations_per_second(&statistics), Some(2.0));
+    }
+}
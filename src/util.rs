@@ -1,13 +1,64 @@
 //! Stores generic util methods that don't really belong in a specific module
 
-use crate::simulation::{WORLD_X_SIZE, WORLD_Y_SIZE};
 use std::{
+    cell::RefCell,
     cmp::Ordering,
+    collections::HashMap,
     ops::{Bound, RangeBounds},
+    sync::{PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard},
 };
 
+use rand::{rngs::StdRng, Rng, RngCore, SeedableRng};
+use uuid::Uuid;
+
 const MAX_RGB: u8 = 255;
 
+thread_local! {
+    static RNG: RefCell<StdRng> = RefCell::new(StdRng::from_entropy());
+}
+
+/// A handle to the current thread's random number generator, see [rng]
+pub struct SeededRng;
+
+impl RngCore for SeededRng {
+    fn next_u32(&mut self) -> u32 {
+        RNG.with(|rng| rng.borrow_mut().next_u32())
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        RNG.with(|rng| rng.borrow_mut().next_u64())
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        RNG.with(|rng| rng.borrow_mut().fill_bytes(dest))
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        RNG.with(|rng| rng.borrow_mut().try_fill_bytes(dest))
+    }
+}
+
+/// Gets the random number generator everything random about creatures comes from. It's per thread, and can be
+/// seeded with [seed_rng] to make creatures repeatable
+pub fn rng() -> SeededRng {
+    SeededRng
+}
+
+/// Seeds the current thread's random number generator, or seeds it from the OS if there's no seed
+pub fn seed_rng(seed: Option<u64>) {
+    let seeded = match seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    };
+
+    RNG.with(|rng| *rng.borrow_mut() = seeded);
+}
+
+/// Creates a random (version 4) id using [rng], so ids are repeatable along with everything else
+pub fn new_uuid() -> Uuid {
+    uuid::Builder::from_random_bytes(rng().gen()).into_uuid()
+}
+
 /// Takes in hsv and outputs rgb, where:
 /// h: [0, 360], s: [0, 100], v: [0, 100]
 /// r: [0, 255], g: [0, 255], b: [0, 255]
@@ -42,29 +93,24 @@ pub fn distance(a: &rapier::prelude::Vector<f32>, b: &rapier::prelude::Vector<f3
     f32::sqrt(f32::powi(a.x - b.x, 2) + f32::powi(a.y - b.y, 2))
 }
 
-/// Converts world x to screen x
-pub fn transform_x_from_world_to_screen(x: f32, screen_size: &egui::Vec2) -> f32 {
-    let x_factor = screen_size.x / WORLD_X_SIZE;
+/// Gets the entries of a map sorted by id. Anything that uses [rng] while going through a map should go through
+/// it like this, since maps are iterated in a different order every run
+pub fn sorted_by_id<T>(map: &HashMap<Uuid, T>) -> Vec<(&Uuid, &T)> {
+    let mut entries: Vec<(&Uuid, &T)> = map.iter().collect();
 
-    x * x_factor
-}
+    entries.sort_by_key(|(id, _)| **id);
 
-/// Converts world y to screen y
-pub fn transform_y_from_world_to_screen(y: f32, screen_size: &egui::Vec2) -> f32 {
-    let y_factor = screen_size.y / WORLD_Y_SIZE;
+    entries
+}
 
-    y * y_factor
+/// Locks a [RwLock] for reading, carrying on if a thread panicked while writing to it
+pub fn read<T>(lock: &RwLock<T>) -> RwLockReadGuard<'_, T> {
+    lock.read().unwrap_or_else(PoisonError::into_inner)
 }
 
-/// Converts physics coordinates to screen coordinates
-pub fn transform_position_from_world_to_screen_pos2(
-    position: &rapier::prelude::Vector<f32>,
-    screen_size: &egui::Vec2,
-) -> egui::Pos2 {
-    egui::Pos2 {
-        x: transform_x_from_world_to_screen(position.x, screen_size),
-        y: transform_y_from_world_to_screen(position.y, screen_size),
-    }
+/// Locks a [RwLock] for writing, carrying on if a thread panicked while writing to it
+pub fn write<T>(lock: &RwLock<T>) -> RwLockWriteGuard<'_, T> {
+    lock.write().unwrap_or_else(PoisonError::into_inner)
 }
 
 /// Compares two f32s a and b
@@ -102,6 +148,38 @@ pub fn clamp_to_range<T: PartialOrd<T> + Clone, R: RangeBounds<T>>(input: T, ran
     input
 }
 
+/// A directory for a test to write files to, unique to each run and removed along with everything in it when
+/// dropped
+#[cfg(test)]
+pub struct TestDirectory {
+    path: std::path::PathBuf,
+}
+
+#[cfg(test)]
+impl TestDirectory {
+    /// Creates an empty directory in the system's temporary directory, named after the test
+    pub fn new(name: &str) -> TestDirectory {
+        let path =
+            std::env::temp_dir().join(format!("project-evolution-{}-{}", name, Uuid::new_v4()));
+
+        std::fs::create_dir_all(&path).unwrap();
+
+        TestDirectory { path }
+    }
+
+    /// Gets the path of the directory
+    pub fn path(&self) -> &std::path::Path {
+        &self.path
+    }
+}
+
+#[cfg(test)]
+impl Drop for TestDirectory {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.path);
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -113,4 +191,16 @@ mod test {
         assert_eq!(hsv_to_rgb(67, 65, 34), (80, 86, 30));
         assert_eq!(hsv_to_rgb(236, 66, 63), (54, 61, 160));
     }
+
+    #[test]
+    pub fn seeded_rng() {
+        seed_rng(Some(7));
+        let first: (u64, Uuid) = (rng().gen(), new_uuid());
+
+        seed_rng(Some(7));
+        let second: (u64, Uuid) = (rng().gen(), new_uuid());
+
+        assert_eq!(first, second);
+        assert_eq!(first.1.get_version_num(), 4);
+    }
 }